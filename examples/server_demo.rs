@@ -11,6 +11,7 @@ async fn main() {
 
     let strategy = VegasStrategy::new(50);
     let flow_layer = FlowGuardLayer::new(strategy);
+    let guard = flow_layer.guard().clone();
 
     let app = Router::new()
         .route("/", get(|| async { "Hello, FlowGuard!" }))
@@ -26,7 +27,20 @@ async fn main() {
         .await
         .unwrap();
     println!("🚀 Servidor rodando em http://127.0.0.1:3000");
-    axum::serve(listener, app).await.unwrap();
+
+    // Ctrl+C: para de aceitar trabalho novo e espera o que está em andamento
+    let shutdown_guard = guard.clone();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            let _ = tokio::signal::ctrl_c().await;
+            println!("🛑 Fechando FlowGuard...");
+            shutdown_guard.close();
+        })
+        .await
+        .unwrap();
+
+    guard.drain().await;
+    println!("✅ Todas as requisições em andamento terminaram");
 }
 
 #[cfg(not(all(feature = "axum", feature = "tower")))]
@@ -0,0 +1,476 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - CLI para simulação, replay e comparação de estratégias
+ */
+
+//! `flowguard`: roda cenários de carga contra um backend modelado, seja na
+//! simulação em tempo virtual (`flow_guard::sim`) ou por HTTP real contra um
+//! servidor axum local, e faz replay de traces gravados.
+//!
+//! ```text
+//! flowguard simulate --strategy vegas --capacity 100 --latency 10ms
+//! flowguard compare --capacity 50 --latency 20ms --distribution exponential
+//! flowguard compare --http --duration 5s
+//! flowguard replay trace.csv --strategy aimd
+//! ```
+
+use axum::error_handling::HandleErrorLayer;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use flow_guard::duration;
+use flow_guard::sim::{Backend, LatencySummary, Scenario, ServiceTime, SimReport};
+use flow_guard::trace::{read_trace, replay};
+use flow_guard::{
+    AimdStrategy, FlowError, FlowGuard, FlowGuardLayer, GradientStrategy, HttpClassifier,
+    LimitStrategy, VegasStrategy,
+};
+use parking_lot::Mutex;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio::time::{interval, Instant};
+use tower::ServiceBuilder;
+
+#[derive(Parser)]
+#[command(
+    name = "flowguard",
+    version,
+    about = "Testes de carga e comparação de estratégias do FlowGuard"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Roda um cenário com uma estratégia e mostra o relatório.
+    Simulate {
+        #[arg(long, value_enum, default_value_t = StrategyKind::Vegas)]
+        strategy: StrategyKind,
+        #[command(flatten)]
+        scenario: ScenarioArgs,
+        /// Imprime também o limite ao longo do tempo.
+        #[arg(long)]
+        show_limits: bool,
+    },
+    /// Alimenta uma estratégia com um trace gravado por `TraceRecorder`.
+    Replay {
+        /// Arquivo CSV do trace.
+        trace: PathBuf,
+        #[arg(long, value_enum, default_value_t = StrategyKind::Vegas)]
+        strategy: StrategyKind,
+        #[arg(long, default_value_t = 10, value_parser = parse_positive)]
+        initial_limit: usize,
+    },
+    /// Roda o mesmo cenário com cada estratégia e imprime uma tabela.
+    Compare {
+        /// Estratégias a comparar, separadas por vírgula (padrão: todas).
+        #[arg(long, value_enum, value_delimiter = ',')]
+        strategies: Vec<StrategyKind>,
+        #[command(flatten)]
+        scenario: ScenarioArgs,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StrategyKind {
+    Vegas,
+    Aimd,
+    Gradient,
+}
+
+impl StrategyKind {
+    fn name(self) -> &'static str {
+        match self {
+            StrategyKind::Vegas => "vegas",
+            StrategyKind::Aimd => "aimd",
+            StrategyKind::Gradient => "gradient",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Distribution {
+    /// Sempre `--latency`.
+    Constant,
+    /// Uniforme entre 0.5x e 1.5x `--latency`.
+    Uniform,
+    /// Exponencial com média `--latency`.
+    Exponential,
+}
+
+#[derive(Args)]
+struct ScenarioArgs {
+    /// Requisições que o backend atende em paralelo.
+    #[arg(long, default_value_t = 100, value_parser = parse_positive)]
+    capacity: usize,
+    /// Tempo de serviço médio do backend (ex.: 10ms, 1.5s, 800us).
+    #[arg(long, default_value = "10ms", value_parser = parse_latency)]
+    latency: Duration,
+    #[arg(long, value_enum, default_value_t = Distribution::Uniform)]
+    distribution: Distribution,
+    /// Requisições por segundo (padrão: 20% acima da capacidade do backend).
+    #[arg(long, value_parser = parse_rate)]
+    rate: Option<f64>,
+    /// Por quanto tempo novas requisições chegam.
+    #[arg(long, default_value = "10s", value_parser = duration::parse)]
+    duration: Duration,
+    /// Fração das requisições que falham no backend.
+    #[arg(long, default_value_t = 0.0, value_parser = parse_failure_rate)]
+    failure_rate: f64,
+    #[arg(long, default_value_t = 10, value_parser = parse_positive)]
+    initial_limit: usize,
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    /// Mede por HTTP real, contra um servidor axum local, em vez de simular
+    /// em tempo virtual. Leva `--duration` de verdade por estratégia.
+    #[arg(long)]
+    http: bool,
+}
+
+impl ScenarioArgs {
+    fn rate(&self) -> f64 {
+        self.rate
+            .unwrap_or_else(|| 1.2 * self.capacity as f64 / self.latency.as_secs_f64())
+    }
+
+    fn backend(&self) -> Backend {
+        let service_time = match self.distribution {
+            Distribution::Constant => ServiceTime::Constant(self.latency),
+            Distribution::Uniform => {
+                ServiceTime::Uniform(self.latency / 2, self.latency.mul_f64(1.5))
+            }
+            Distribution::Exponential => ServiceTime::Exponential(self.latency),
+        };
+        Backend::new(self.capacity, service_time).with_failure_rate(self.failure_rate)
+    }
+
+    fn run(&self, strategy: StrategyKind) -> Result<SimReport, Box<dyn Error>> {
+        let limit = self.initial_limit;
+        match strategy {
+            StrategyKind::Vegas => self.run_with(VegasStrategy::new(limit)),
+            StrategyKind::Aimd => self.run_with(AimdStrategy::new(limit)),
+            StrategyKind::Gradient => self.run_with(GradientStrategy::new(limit)),
+        }
+    }
+
+    fn run_with<S: LimitStrategy + 'static>(
+        &self,
+        strategy: S,
+    ) -> Result<SimReport, Box<dyn Error>> {
+        let guard = FlowGuard::new(strategy);
+        if !self.http {
+            let scenario = Scenario::new(self.backend())
+                .with_arrival_rate(self.rate())
+                .with_duration(self.duration)
+                .with_seed(self.seed);
+            return Ok(scenario.simulate(guard));
+        }
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        Ok(runtime.block_on(self.load_test(guard))?)
+    }
+
+    /// Sobe o backend atrás de um servidor axum protegido pelo guard e gera
+    /// requisições em intervalo fixo.
+    async fn load_test<S: LimitStrategy + 'static>(
+        &self,
+        guard: FlowGuard<S>,
+    ) -> io::Result<SimReport> {
+        let guard = Arc::new(guard);
+        let backend = Arc::new(self.backend().start(self.seed));
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(move || {
+                    let backend = Arc::clone(&backend);
+                    async move {
+                        match backend.call().await {
+                            Ok(()) => StatusCode::OK,
+                            Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                        }
+                    }
+                }),
+            )
+            .layer(
+                ServiceBuilder::new()
+                    .layer(HandleErrorLayer::new(
+                        |err: FlowError<Infallible>| async move { err.into_response() },
+                    ))
+                    .layer(
+                        FlowGuardLayer::from_guard(Arc::clone(&guard))
+                            .with_classifier(HttpClassifier),
+                    ),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let counters = Arc::new(Counters::default());
+        let latencies = Arc::new(Mutex::new(Vec::new()));
+        let start = Instant::now();
+        let end = start + self.duration;
+
+        let mut limit_trace = Vec::new();
+        let mut sample = interval(Duration::from_millis(100));
+        // Taxas muito altas dariam intervalo zero, que o `interval` recusa
+        let period = Duration::from_secs_f64(1.0 / self.rate()).max(Duration::from_nanos(1));
+        let mut arrivals = interval(period);
+        let mut requests = JoinSet::new();
+        loop {
+            tokio::select! {
+                now = sample.tick() => {
+                    limit_trace.push((now - start, guard.current_limit()));
+                }
+                now = arrivals.tick() => {
+                    if now >= end {
+                        break;
+                    }
+                    counters.offered.fetch_add(1, Ordering::Relaxed);
+
+                    let counters = Arc::clone(&counters);
+                    let latencies = Arc::clone(&latencies);
+                    requests.spawn(async move {
+                        let sent = Instant::now();
+                        match http_get(addr).await {
+                            Ok(200) => {
+                                counters.succeeded.fetch_add(1, Ordering::Relaxed);
+                                latencies.lock().push(sent.elapsed());
+                            }
+                            Ok(503) => {
+                                counters.rejected.fetch_add(1, Ordering::Relaxed);
+                            }
+                            _ => {
+                                counters.failed.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+
+        while requests.join_next().await.is_some() {}
+        let elapsed = start.elapsed();
+        server.abort();
+
+        let succeeded = counters.succeeded.load(Ordering::Relaxed);
+        let latencies = std::mem::take(&mut *latencies.lock());
+        Ok(SimReport {
+            offered: counters.offered.load(Ordering::Relaxed),
+            succeeded,
+            failed: counters.failed.load(Ordering::Relaxed),
+            rejected: counters.rejected.load(Ordering::Relaxed),
+            goodput: succeeded as f64 / elapsed.as_secs_f64(),
+            latency: LatencySummary::from_latencies(latencies),
+            limit_trace,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    offered: AtomicU64,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// GET mínimo em HTTP/1.1, uma conexão por requisição. Retorna o status.
+async fn http_get(addr: SocketAddr) -> io::Result<u16> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+
+    // Linha de status: "HTTP/1.1 200 OK"
+    String::from_utf8_lossy(&response)
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response"))
+}
+
+/// Tempo de serviço: zero faria o backend simulado nunca avançar o relógio.
+fn parse_latency(value: &str) -> Result<Duration, String> {
+    let latency = duration::parse(value)?;
+    if latency.is_zero() {
+        return Err("latency must be greater than zero".to_string());
+    }
+    Ok(latency)
+}
+
+/// Capacidade e limite inicial: pelo menos 1.
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(number) => Ok(number),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    let rate: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid number in {value:?}"))?;
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err("rate must be a finite number greater than zero".to_string());
+    }
+    Ok(rate)
+}
+
+/// Fração de falhas: uma probabilidade, em `[0, 1]`.
+fn parse_failure_rate(value: &str) -> Result<f64, String> {
+    let rate: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid number in {value:?}"))?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err("failure rate must be between 0 and 1".to_string());
+    }
+    Ok(rate)
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1_000.0)
+}
+
+fn print_report(strategy: StrategyKind, report: &SimReport, show_limits: bool) {
+    let final_limit = report.limit_trace.last().map_or(0, |&(_, limit)| limit);
+
+    println!("strategy     {}", strategy.name());
+    println!("offered      {}", report.offered);
+    println!("succeeded    {}", report.succeeded);
+    println!("failed       {}", report.failed);
+    println!("rejected     {}", report.rejected);
+    println!("goodput      {:.1} req/s", report.goodput);
+    println!(
+        "latency      p50 {}  p90 {}  p99 {}  max {}",
+        millis(report.latency.p50),
+        millis(report.latency.p90),
+        millis(report.latency.p99),
+        millis(report.latency.max)
+    );
+    println!("final limit  {final_limit}");
+
+    if show_limits {
+        println!();
+        println!("{:>10}  {:>6}", "time_ms", "limit");
+        for (time, limit) in &report.limit_trace {
+            println!("{:>10}  {:>6}", time.as_millis(), limit);
+        }
+    }
+}
+
+fn print_comparison(rows: &[(StrategyKind, SimReport)]) {
+    println!(
+        "{:<10} {:>14} {:>10} {:>10} {:>10} {:>12}",
+        "strategy", "goodput req/s", "p99", "rejected", "failed", "final limit"
+    );
+    for (strategy, report) in rows {
+        let final_limit = report.limit_trace.last().map_or(0, |&(_, limit)| limit);
+        println!(
+            "{:<10} {:>14.1} {:>10} {:>10} {:>10} {:>12}",
+            strategy.name(),
+            report.goodput,
+            millis(report.latency.p99),
+            report.rejected,
+            report.failed,
+            final_limit
+        );
+    }
+}
+
+fn replay_trace(
+    path: &Path,
+    strategy: StrategyKind,
+    initial_limit: usize,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::open(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let samples = read_trace(BufReader::new(file))?;
+    let timeline = match strategy {
+        StrategyKind::Vegas => replay(&VegasStrategy::new(initial_limit), &samples),
+        StrategyKind::Aimd => replay(&AimdStrategy::new(initial_limit), &samples),
+        StrategyKind::Gradient => replay(&GradientStrategy::new(initial_limit), &samples),
+    };
+
+    println!("{:>10}  {:>6}  {:>9}", "time_ms", "limit", "in_flight");
+    for point in &timeline {
+        println!(
+            "{:>10}  {:>6}  {:>9}",
+            point.timestamp.as_millis(),
+            point.limit,
+            point.in_flight
+        );
+    }
+
+    let limits = timeline.iter().map(|point| point.limit);
+    println!();
+    println!(
+        "{} samples, {} changes, limit {}..{}, final {}",
+        samples.len(),
+        timeline.len() - 1,
+        limits.clone().min().unwrap_or(0),
+        limits.max().unwrap_or(0),
+        timeline.last().map_or(0, |point| point.limit)
+    );
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    match cli.command {
+        Command::Simulate {
+            strategy,
+            scenario,
+            show_limits,
+        } => {
+            let report = scenario.run(strategy)?;
+            print_report(strategy, &report, show_limits);
+        }
+        Command::Replay {
+            trace,
+            strategy,
+            initial_limit,
+        } => replay_trace(&trace, strategy, initial_limit)?,
+        Command::Compare {
+            mut strategies,
+            scenario,
+        } => {
+            if strategies.is_empty() {
+                strategies = StrategyKind::value_variants().to_vec();
+            }
+            let rows = strategies
+                .into_iter()
+                .map(|strategy| Ok((strategy, scenario.run(strategy)?)))
+                .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+            print_comparison(&rows);
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
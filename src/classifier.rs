@@ -0,0 +1,69 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Classificação de resultados
+ */
+
+use crate::token::Outcome;
+
+/// Decide como o resultado de uma execução protegida alimenta a estratégia.
+///
+/// Nem todo `Err` é congestionamento (um 404 ou erro de validação não diz nada
+/// sobre a carga) e nem todo `Ok` é saudável (um 503 vindo de um serviço
+/// downstream é sinal de sobrecarga).
+///
+/// Closures `Fn(&Result<T, E>) -> Outcome` também implementam o trait.
+pub trait Classifier<T, E>: Send + Sync {
+    fn classify(&self, result: &Result<T, E>) -> Outcome;
+}
+
+impl<T, E, F> Classifier<T, E> for F
+where
+    F: Fn(&Result<T, E>) -> Outcome + Send + Sync,
+{
+    fn classify(&self, result: &Result<T, E>) -> Outcome {
+        self(result)
+    }
+}
+
+/// Comportamento padrão: `Ok` é sucesso e qualquer `Err` é sobrecarga.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultClassifier;
+
+impl<T, E> Classifier<T, E> for DefaultClassifier {
+    fn classify(&self, result: &Result<T, E>) -> Outcome {
+        match result {
+            Ok(_) => Outcome::Success,
+            Err(_) => Outcome::Dropped,
+        }
+    }
+}
+
+/// Classificador para serviços HTTP.
+///
+/// - 5xx e 429 (Too Many Requests): sobrecarga
+/// - demais 4xx: ignorados (erro do cliente, não da carga)
+/// - qualquer outro status: sucesso
+/// - `Err` do serviço: sobrecarga
+#[cfg(feature = "axum")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpClassifier;
+
+#[cfg(feature = "axum")]
+impl<B, E> Classifier<axum::http::Response<B>, E> for HttpClassifier {
+    fn classify(&self, result: &Result<axum::http::Response<B>, E>) -> Outcome {
+        use axum::http::StatusCode;
+
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                    Outcome::Dropped
+                } else if status.is_client_error() {
+                    Outcome::Ignore
+                } else {
+                    Outcome::Success
+                }
+            }
+            Err(_) => Outcome::Dropped,
+        }
+    }
+}
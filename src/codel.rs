@@ -0,0 +1,79 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Gerenciamento da fila por CoDel (Controlled Delay)
+ */
+
+use parking_lot::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Timeout de fila adaptativo no estilo CoDel, como usado em servidores.
+///
+/// A cada `interval` olha o menor tempo de espera na fila (sojourn) visto no
+/// intervalo. Se até o mais rápido esperou mais que `target`, existe uma fila
+/// que não esvazia: no intervalo seguinte os waiters desistem após `target`.
+/// Caso contrário o timeout é o próprio `interval`, tolerando rajadas.
+///
+/// Aquisições que não esperaram contam como sojourn zero, então basta a fila
+/// esvaziar uma vez para o modo sobrecarregado terminar no intervalo seguinte.
+#[derive(Debug)]
+pub(crate) struct CoDel {
+    target: Duration,
+    interval: Duration,
+    state: Mutex<CoDelState>,
+}
+
+#[derive(Debug)]
+struct CoDelState {
+    interval_start: Instant,
+    min_sojourn: Option<Duration>,
+    overloaded: bool,
+}
+
+impl CoDel {
+    pub(crate) fn new(target: Duration, interval: Duration) -> Self {
+        Self {
+            target,
+            interval,
+            state: Mutex::new(CoDelState {
+                interval_start: Instant::now(),
+                min_sojourn: None,
+                overloaded: false,
+            }),
+        }
+    }
+
+    /// Timeout a aplicar a quem vai entrar na fila agora.
+    pub(crate) fn queue_timeout(&self) -> Duration {
+        if self.is_overloaded() {
+            self.target
+        } else {
+            self.interval
+        }
+    }
+
+    pub(crate) fn is_overloaded(&self) -> bool {
+        let mut state = self.state.lock();
+        self.roll_interval(&mut state);
+        state.overloaded
+    }
+
+    /// Registra quanto tempo uma aquisição passou na fila (inclusive as que
+    /// desistiram por timeout).
+    pub(crate) fn record_sojourn(&self, sojourn: Duration) {
+        let mut state = self.state.lock();
+        self.roll_interval(&mut state);
+        state.min_sojourn = Some(state.min_sojourn.map_or(sojourn, |min| min.min(sojourn)));
+    }
+
+    fn roll_interval(&self, state: &mut CoDelState) {
+        let now = Instant::now();
+        if now.duration_since(state.interval_start) < self.interval {
+            return;
+        }
+
+        // Sem amostras no intervalo não há evidência de fila parada
+        state.overloaded = state.min_sojourn.is_some_and(|min| min > self.target);
+        state.min_sojourn = None;
+        state.interval_start = now;
+    }
+}
@@ -0,0 +1,640 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Configuração serializável (feature "serde")
+ */
+
+//! Configuração de estratégias e guards a partir de arquivos (TOML, YAML,
+//! JSON, ...), para trocar de algoritmo e de parâmetros sem recompilar.
+//!
+//! Durações são escritas como texto com unidade: `"800us"`, `"250ms"`, `"2s"`.
+//! Campos omitidos assumem os mesmos padrões dos construtores.
+//!
+//! ```
+//! use flow_guard::{FlowGuard, FlowGuardConfig};
+//!
+//! let config: FlowGuardConfig = toml::from_str(
+//!     r#"
+//!     name = "payments"
+//!     max_queue = 200
+//!     queue_timeout = "500ms"
+//!
+//!     [strategy]
+//!     type = "vegas"
+//!     initial_limit = 20
+//!     alpha = 3.0
+//!     beta = 6.0
+//!     "#,
+//! )
+//! .unwrap();
+//!
+//! let guard = FlowGuard::from_config(&config).unwrap();
+//! assert_eq!(guard.name(), "payments");
+//! assert_eq!(guard.current_limit(), 20);
+//! ```
+
+use crate::criticality::Criticality;
+use crate::error::ConfigError;
+use crate::strategy::{
+    Aggregation, AimdStrategy, FixedStrategy, GradientStrategy, VegasStrategy, WindowedStrategy,
+};
+use crate::{FlowGuard, QueueDiscipline, ReloadableStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+
+/// Estratégia e seus parâmetros. O campo `type` escolhe a variante.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StrategyConfig {
+    Vegas(VegasConfig),
+    Aimd(AimdConfig),
+    Gradient(GradientConfig),
+    Fixed(FixedConfig),
+    Windowed(WindowedConfig),
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        StrategyConfig::Vegas(VegasConfig::default())
+    }
+}
+
+impl StrategyConfig {
+    /// Valor do campo `type`: `"vegas"`, `"aimd"`, ...
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StrategyConfig::Vegas(_) => "vegas",
+            StrategyConfig::Aimd(_) => "aimd",
+            StrategyConfig::Gradient(_) => "gradient",
+            StrategyConfig::Fixed(_) => "fixed",
+            StrategyConfig::Windowed(_) => "windowed",
+        }
+    }
+
+    /// Constrói a estratégia, validando os parâmetros.
+    pub fn build(&self) -> Result<Box<dyn ReloadableStrategy>, ConfigError> {
+        Ok(match self {
+            StrategyConfig::Vegas(config) => Box::new(config.build()?),
+            StrategyConfig::Aimd(config) => Box::new(config.build()?),
+            StrategyConfig::Gradient(config) => Box::new(config.build()?),
+            StrategyConfig::Fixed(config) => Box::new(config.build()?),
+            StrategyConfig::Windowed(config) => Box::new(config.build()?),
+        })
+    }
+}
+
+/// Parâmetros de [`VegasStrategy`]; veja `VegasBuilder`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VegasConfig {
+    pub initial_limit: usize,
+    pub min_limit: usize,
+    /// Padrão: 10x `initial_limit`.
+    pub max_limit: Option<usize>,
+    #[serde(with = "duration")]
+    pub initial_base_rtt: Duration,
+    pub alpha: LimitFnConfig,
+    pub beta: LimitFnConfig,
+    pub threshold: Option<LimitFnConfig>,
+    pub base_rtt_window: Option<usize>,
+    /// Sondagem periódica do RTT base (desligada se ausente).
+    pub probe: Option<ProbeConfig>,
+}
+
+impl Default for VegasConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 10,
+            min_limit: 1,
+            max_limit: None,
+            initial_base_rtt: Duration::from_millis(1000),
+            alpha: LimitFnConfig::Constant(2.0),
+            beta: LimitFnConfig::Constant(4.0),
+            threshold: None,
+            base_rtt_window: None,
+            probe: None,
+        }
+    }
+}
+
+/// `alpha`, `beta` ou `threshold` do Vegas: um número (constante) ou uma
+/// função do limite atual, como no Vegas de referência.
+///
+/// ```toml
+/// [strategy]
+/// type = "vegas"
+/// alpha = { log10 = 3.0 }   # 3 * log10(limite)
+/// beta = { log10 = 6.0 }
+/// threshold = { log10 = 1.0 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum LimitFnConfig {
+    Constant(f64),
+    /// `log10 * log10(limite)`.
+    Log10 {
+        log10: f64,
+    },
+}
+
+impl LimitFnConfig {
+    fn to_fn(self) -> impl Fn(usize) -> f64 + Send + Sync + 'static {
+        move |limit| match self {
+            LimitFnConfig::Constant(value) => value,
+            LimitFnConfig::Log10 { log10 } => log10 * (limit as f64).log10(),
+        }
+    }
+}
+
+impl From<f64> for LimitFnConfig {
+    fn from(value: f64) -> Self {
+        LimitFnConfig::Constant(value)
+    }
+}
+
+/// Sondagem do Vegas; veja `VegasBuilder::probe_interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProbeConfig {
+    pub interval: usize,
+    pub jitter: f64,
+    /// Padrão: `min_limit`.
+    pub limit: Option<usize>,
+    pub samples: usize,
+    /// Semente do jitter (padrão: aleatória).
+    pub seed: Option<u64>,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1000,
+            jitter: 0.5,
+            limit: None,
+            samples: 10,
+            seed: None,
+        }
+    }
+}
+
+impl VegasConfig {
+    pub fn build(&self) -> Result<VegasStrategy, ConfigError> {
+        let mut builder = VegasStrategy::builder()
+            .initial_limit(self.initial_limit)
+            .min_limit(self.min_limit)
+            .initial_base_rtt(self.initial_base_rtt)
+            .alpha_fn(self.alpha.to_fn())
+            .beta_fn(self.beta.to_fn());
+        if let Some(max_limit) = self.max_limit {
+            builder = builder.max_limit(max_limit);
+        }
+        if let Some(threshold) = self.threshold {
+            builder = builder.threshold_fn(threshold.to_fn());
+        }
+        if let Some(window) = self.base_rtt_window {
+            builder = builder.base_rtt_window(window);
+        }
+        if let Some(probe) = &self.probe {
+            builder = builder
+                .probe_interval(probe.interval)
+                .probe_jitter(probe.jitter)
+                .probe_samples(probe.samples);
+            if let Some(limit) = probe.limit {
+                builder = builder.probe_limit(limit);
+            }
+            if let Some(seed) = probe.seed {
+                builder = builder.probe_seed(seed);
+            }
+        }
+        builder.build()
+    }
+}
+
+/// Parâmetros de [`AimdStrategy`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AimdConfig {
+    pub initial_limit: usize,
+    pub min_limit: usize,
+    /// Padrão: 10x `initial_limit`.
+    pub max_limit: Option<usize>,
+    pub increase: usize,
+    pub backoff_ratio: f64,
+    #[serde(with = "duration")]
+    pub timeout: Duration,
+}
+
+impl Default for AimdConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 10,
+            min_limit: 1,
+            max_limit: None,
+            increase: 1,
+            backoff_ratio: 0.9,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl AimdConfig {
+    pub fn build(&self) -> Result<AimdStrategy, ConfigError> {
+        let max_limit = check_limits(self.initial_limit, self.min_limit, self.max_limit)?;
+        if !(self.backoff_ratio > 0.0 && self.backoff_ratio < 1.0) {
+            return Err(invalid(
+                "backoff_ratio",
+                "must be between 0.0 and 1.0 (exclusive)",
+            ));
+        }
+
+        Ok(AimdStrategy::new(self.initial_limit)
+            .with_min_limit(self.min_limit)
+            .with_max_limit(max_limit)
+            .with_increase(self.increase)
+            .with_backoff_ratio(self.backoff_ratio)
+            .with_timeout(self.timeout))
+    }
+}
+
+/// Parâmetros de [`GradientStrategy`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GradientConfig {
+    pub initial_limit: usize,
+    pub min_limit: usize,
+    /// Padrão: 10x `initial_limit`.
+    pub max_limit: Option<usize>,
+    pub smoothing: f64,
+    pub tolerance: f64,
+    pub queue_size: usize,
+    pub long_window: usize,
+}
+
+impl Default for GradientConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 10,
+            min_limit: 1,
+            max_limit: None,
+            smoothing: 0.2,
+            tolerance: 1.5,
+            queue_size: 4,
+            long_window: 600,
+        }
+    }
+}
+
+impl GradientConfig {
+    pub fn build(&self) -> Result<GradientStrategy, ConfigError> {
+        let max_limit = check_limits(self.initial_limit, self.min_limit, self.max_limit)?;
+        if !(self.smoothing > 0.0 && self.smoothing <= 1.0) {
+            return Err(invalid(
+                "smoothing",
+                "must be between 0.0 (exclusive) and 1.0",
+            ));
+        }
+        if self.tolerance.is_nan() || self.tolerance < 1.0 {
+            return Err(invalid("tolerance", "must be at least 1.0"));
+        }
+        if self.long_window == 0 {
+            return Err(invalid("long_window", "must be at least 1"));
+        }
+
+        Ok(GradientStrategy::new(self.initial_limit)
+            .with_min_limit(self.min_limit)
+            .with_max_limit(max_limit)
+            .with_smoothing(self.smoothing)
+            .with_tolerance(self.tolerance)
+            .with_queue_size(self.queue_size)
+            .with_long_window(self.long_window))
+    }
+}
+
+/// Parâmetros de [`FixedStrategy`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FixedConfig {
+    pub limit: usize,
+}
+
+impl FixedConfig {
+    pub fn build(&self) -> Result<FixedStrategy, ConfigError> {
+        if self.limit == 0 {
+            return Err(invalid("limit", "must be at least 1"));
+        }
+        Ok(FixedStrategy::new(self.limit))
+    }
+}
+
+/// Parâmetros de [`WindowedStrategy`], envolvendo outra estratégia.
+///
+/// ```toml
+/// [strategy]
+/// type = "windowed"
+/// samples = 50
+/// aggregation = { percentile = 90.0 }
+///
+/// [strategy.inner]
+/// type = "gradient"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindowedConfig {
+    pub inner: Box<StrategyConfig>,
+    #[serde(default = "WindowedConfig::default_samples")]
+    pub samples: usize,
+    #[serde(default, with = "option_duration")]
+    pub duration: Option<Duration>,
+    #[serde(default = "WindowedConfig::default_aggregation")]
+    pub aggregation: Aggregation,
+    /// Fração de erros que faz a janela virar um `on_error()`.
+    #[serde(default = "WindowedConfig::default_error_threshold")]
+    pub error_threshold: f64,
+}
+
+impl WindowedConfig {
+    fn default_samples() -> usize {
+        100
+    }
+
+    fn default_aggregation() -> Aggregation {
+        Aggregation::Average
+    }
+
+    fn default_error_threshold() -> f64 {
+        0.1
+    }
+
+    /// Valida os parâmetros da janela (sem a estratégia interna).
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        if self.samples == 0 {
+            return Err(invalid("samples", "must be at least 1"));
+        }
+        if let Aggregation::Percentile(percentile) = self.aggregation {
+            if !(0.0..=100.0).contains(&percentile) {
+                return Err(invalid("percentile", "must be between 0.0 and 100.0"));
+            }
+        }
+        if !(0.0..1.0).contains(&self.error_threshold) {
+            return Err(invalid(
+                "error_threshold",
+                "must be between 0.0 and 1.0 (exclusive)",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn build(&self) -> Result<WindowedStrategy<Box<dyn ReloadableStrategy>>, ConfigError> {
+        self.validate()?;
+
+        let mut strategy = WindowedStrategy::new(self.inner.build()?)
+            .with_sample_window(self.samples)
+            .with_aggregation(self.aggregation)
+            .with_error_threshold(self.error_threshold);
+        if let Some(duration) = self.duration {
+            strategy = strategy.with_time_window(duration);
+        }
+        Ok(strategy)
+    }
+}
+
+/// Configuração completa de um [`FlowGuard`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FlowGuardConfig {
+    /// Padrão: `flow_guard`.
+    pub name: Option<String>,
+    pub strategy: StrategyConfig,
+    pub max_queue: Option<usize>,
+    #[serde(with = "option_duration")]
+    pub queue_timeout: Option<Duration>,
+    pub codel: Option<CoDelConfig>,
+    pub queue_discipline: QueueDiscipline,
+    /// Limiares de utilização por criticidade (`sheddable = 0.7`).
+    pub criticality_thresholds: HashMap<Criticality, f64>,
+    pub tracing_span: bool,
+}
+
+/// Parâmetros de `FlowGuard::with_codel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoDelConfig {
+    #[serde(with = "duration")]
+    pub target: Duration,
+    #[serde(with = "duration")]
+    pub interval: Duration,
+}
+
+impl CoDelConfig {
+    /// Com `interval` zero toda espera falharia na hora; `target` acima de
+    /// `interval` nunca seria atingido antes do timeout da fila.
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        if self.target.is_zero() {
+            return Err(invalid("codel.target", "must be greater than zero"));
+        }
+        if self.interval.is_zero() {
+            return Err(invalid("codel.interval", "must be greater than zero"));
+        }
+        if self.target > self.interval {
+            return Err(invalid("codel.target", "must not exceed codel.interval"));
+        }
+        Ok(())
+    }
+}
+
+impl FlowGuard<Box<dyn ReloadableStrategy>> {
+    /// Constrói um guard com a estratégia escolhida em `config`.
+    ///
+    /// Parâmetros inválidos viram `ConfigError` em vez de pânico, já que vêm
+    /// de fora do programa.
+    pub fn from_config(config: &FlowGuardConfig) -> Result<Self, ConfigError> {
+        let mut guard =
+            FlowGuard::new(config.strategy.build()?).with_queue_discipline(config.queue_discipline);
+
+        if let Some(name) = &config.name {
+            guard = guard.with_name(name.clone());
+        }
+        if let Some(max_queue) = config.max_queue {
+            guard = guard.with_max_queue(max_queue);
+        }
+        if let Some(timeout) = config.queue_timeout {
+            guard = guard.with_queue_timeout(timeout);
+        }
+        if let Some(codel) = config.codel {
+            codel.validate()?;
+            guard = guard.with_codel(codel.target, codel.interval);
+        }
+        for (&criticality, &threshold) in &config.criticality_thresholds {
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                return Err(invalid("criticality_thresholds", "must be in (0, 1]"));
+            }
+            guard = guard.with_criticality_threshold(criticality, threshold);
+        }
+        if config.tracing_span {
+            guard = guard.with_tracing_span();
+        }
+        Ok(guard)
+    }
+}
+
+/// Relê `path` a cada `poll_interval` e publica o resultado de `parse` sempre
+/// que o conteúdo muda. Combine com `FlowGuard::reload_from`.
+///
+/// A primeira leitura acontece antes de retornar, e falhas nela viram erro.
+/// Depois disso, arquivos ilegíveis ou inválidos geram `tracing::warn!` e o
+/// último valor válido continua publicado. A tarefa de polling termina quando
+/// todos os receivers são descartados, inclusive o entregue a
+/// `FlowGuard::reload_from`, que só é solto quando o guard é fechado ou a
+/// tarefa de recarga é abortada.
+///
+/// ```no_run
+/// use flow_guard::config::watch_file;
+/// use flow_guard::{FlowGuard, FlowGuardConfig};
+/// use std::time::Duration;
+///
+/// # async fn demo() -> std::io::Result<()> {
+/// let configs = watch_file("flowguard.toml", Duration::from_secs(5), |text| {
+///     toml::from_str::<FlowGuardConfig>(text)
+/// })
+/// .await?;
+///
+/// let guard = FlowGuard::from_config(&configs.borrow()).expect("invalid config");
+/// let reload = guard.reload_from(configs);
+///
+/// // No desligamento: fechar o guard encerra a recarga e, com ela, o polling
+/// guard.close();
+/// reload.await.expect("reload task panicked");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn watch_file<T, E, F>(
+    path: impl Into<PathBuf>,
+    poll_interval: Duration,
+    parse: F,
+) -> io::Result<watch::Receiver<T>>
+where
+    T: Send + Sync + 'static,
+    E: fmt::Display,
+    F: Fn(&str) -> Result<T, E> + Send + 'static,
+{
+    let path = path.into();
+    let mut contents = tokio::fs::read_to_string(&path).await?;
+    let value = parse(&contents).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {err}", path.display()),
+        )
+    })?;
+
+    let (sender, receiver) = watch::channel(value);
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(poll_interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticks.tick().await; // o primeiro tick é imediato
+        let mut unreadable = false;
+
+        loop {
+            tokio::select! {
+                _ = sender.closed() => break,
+                _ = ticks.tick() => {}
+            }
+
+            let text = match tokio::fs::read_to_string(&path).await {
+                Ok(text) => {
+                    unreadable = false;
+                    text
+                }
+                Err(err) => {
+                    // Um aviso por falha, não um por polling (ex.: arquivo sendo trocado)
+                    if !unreadable {
+                        tracing::warn!(path = %path.display(), error = %err, "cannot read config file");
+                        unreadable = true;
+                    }
+                    continue;
+                }
+            };
+            if text == contents {
+                continue;
+            }
+
+            match parse(&text) {
+                Ok(value) => {
+                    tracing::info!(path = %path.display(), "config file changed");
+                    sender.send_replace(value);
+                }
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), error = %err, "ignoring invalid config file");
+                }
+            }
+            contents = text;
+        }
+    });
+
+    Ok(receiver)
+}
+
+/// Mesmas regras de `VegasBuilder::build`; devolve o `max_limit` efetivo.
+fn check_limits(initial: usize, min: usize, max: Option<usize>) -> Result<usize, ConfigError> {
+    let max = max.unwrap_or(initial.saturating_mul(10));
+    if min == 0 {
+        return Err(ConfigError::ZeroMinLimit);
+    }
+    if min > max {
+        return Err(ConfigError::MinAboveMax { min, max });
+    }
+    if initial < min || initial > max {
+        return Err(ConfigError::InitialOutOfBounds { initial, min, max });
+    }
+    Ok(max)
+}
+
+fn invalid(name: &'static str, reason: &'static str) -> ConfigError {
+    ConfigError::InvalidParameter { name, reason }
+}
+
+/// Serde das durações em texto de [`crate::duration`].
+pub(crate) mod duration {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&crate::duration::format(*duration))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        crate::duration::parse(&text).map_err(de::Error::custom)
+    }
+}
+
+mod option_duration {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&crate::duration::format(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| crate::duration::parse(&text).map_err(de::Error::custom))
+            .transpose()
+    }
+}
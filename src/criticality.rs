@@ -0,0 +1,50 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Níveis de criticidade das execuções
+ */
+
+/// Importância de uma execução quando o limite aperta.
+///
+/// A fila de espera é ordenada por criticidade (e por ordem de chegada dentro
+/// do mesmo nível), e cada nível pode ter um limiar de utilização acima do
+/// qual é rejeitado na hora (`FlowGuard::with_criticality_threshold`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Criticality {
+    /// Nunca deve ser descartado antes dos demais (health checks, controle).
+    Critical,
+    /// Tráfego prioritário (clientes pagantes, fluxo principal).
+    High,
+    /// Padrão de `run`/`acquire`.
+    #[default]
+    Normal,
+    /// Pode ser descartado primeiro (batch, tarefas em segundo plano).
+    Sheddable,
+}
+
+impl Criticality {
+    /// Todos os níveis, do mais para o menos importante.
+    pub const ALL: [Criticality; 4] = [
+        Criticality::Critical,
+        Criticality::High,
+        Criticality::Normal,
+        Criticality::Sheddable,
+    ];
+
+    /// Posição na fila: 0 é atendido primeiro.
+    pub(crate) fn rank(self) -> usize {
+        self as usize
+    }
+
+    /// Limiar de utilização padrão: só `Sheddable` começa a ser rejeitado
+    /// antes do limite, a partir de 80% dele.
+    pub(crate) fn default_threshold(self) -> f64 {
+        match self {
+            Criticality::Sheddable => 0.8,
+            _ => 1.0,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Durações em texto
+ */
+
+//! Durações escritas como texto com unidade: `"800us"`, `"250ms"`, `"1.5s"`.
+//! É o formato dos arquivos de configuração e das opções da CLI.
+//!
+//! ```
+//! use flow_guard::duration;
+//! use std::time::Duration;
+//!
+//! assert_eq!(duration::parse("1.5s"), Ok(Duration::from_millis(1500)));
+//! assert_eq!(duration::format(Duration::from_micros(800)), "800us");
+//! assert!(duration::parse("10").is_err());
+//! ```
+
+use std::time::Duration;
+
+/// Lê uma duração com unidade `us`, `ms` ou `s`.
+pub fn parse(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let unit_start = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(|| format!("missing unit in duration {text:?} (use us, ms or s)"))?;
+    let (number, unit) = text.split_at(unit_start);
+
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid duration {text:?}"))?;
+    let seconds = match unit {
+        "us" => number / 1_000_000.0,
+        "ms" => number / 1_000.0,
+        "s" => number,
+        _ => return Err(format!("unknown unit {unit:?} (use us, ms or s)")),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|err| format!("{text:?}: {err}"))
+}
+
+/// Escreve `duration` na maior unidade que a representa sem perda (até
+/// microssegundos).
+pub fn format(duration: Duration) -> String {
+    if duration.subsec_nanos() == 0 {
+        format!("{}s", duration.as_secs())
+    } else if duration.subsec_millis() * 1_000_000 == duration.subsec_nanos() {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{}us", duration.as_micros())
+    }
+}
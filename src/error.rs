@@ -10,6 +10,10 @@ use thiserror::Error;
 pub enum FlowError<E> {
     #[error("Request dropped due to high load")]
     Dropped,
+    #[error("Request rejected: FlowGuard wait queue is full")]
+    QueueFull,
+    #[error("Request timed out waiting in the FlowGuard queue")]
+    QueueTimeout,
     #[error("FlowGuard semaphore closed")]
     Closed,
     #[error("Application error: {0}")]
@@ -28,8 +32,69 @@ where
                 "Service Overloaded - Try again later",
             )
                 .into_response(),
-            Self::Closed => (StatusCode::INTERNAL_SERVER_ERROR, "FlowGuard Closed").into_response(),
+            Self::QueueFull => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Service Overloaded - Queue full",
+            )
+                .into_response(),
+            Self::QueueTimeout => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Service Overloaded - Queue timeout",
+            )
+                .into_response(),
+            // Fechado = desligamento em andamento: o cliente pode tentar outra instância
+            Self::Closed => (StatusCode::SERVICE_UNAVAILABLE, "FlowGuard Closed").into_response(),
             Self::AppError(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
         }
     }
 }
+
+/// Parâmetros inválidos ao construir uma estratégia.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("min_limit must be at least 1")]
+    ZeroMinLimit,
+    #[error("min_limit ({min}) is greater than max_limit ({max})")]
+    MinAboveMax { min: usize, max: usize },
+    #[error("initial_limit ({initial}) must be between min_limit ({min}) and max_limit ({max})")]
+    InitialOutOfBounds {
+        initial: usize,
+        min: usize,
+        max: usize,
+    },
+    #[error("initial_base_rtt must be greater than zero")]
+    ZeroBaseRtt,
+    #[error("{name} must be finite and non-negative, got {value} at limit {limit}")]
+    InvalidThreshold {
+        name: &'static str,
+        value: f64,
+        limit: usize,
+    },
+    #[error("alpha ({alpha}) is greater than beta ({beta}) at limit {limit}")]
+    AlphaAboveBeta { alpha: f64, beta: f64, limit: usize },
+    #[error("{name} {reason}")]
+    InvalidParameter {
+        name: &'static str,
+        reason: &'static str,
+    },
+    #[error("threshold ({threshold}) is greater than alpha ({alpha}) at limit {limit}")]
+    ThresholdAboveAlpha {
+        threshold: f64,
+        alpha: f64,
+        limit: usize,
+    },
+    #[error("cannot reload a {expected} strategy with {found} settings")]
+    StrategyMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+/// Erro ao ler um trace gravado por `trace::TraceRecorder`.
+#[derive(Error, Debug)]
+pub enum TraceError {
+    #[error("failed to read trace: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid trace line {line}: {reason}")]
+    Parse { line: usize, reason: String },
+}
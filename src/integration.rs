@@ -2,69 +2,126 @@
  * FlowGuard - Tower/Axum Integration Layer
  */
 
+use crate::classifier::{Classifier, DefaultClassifier};
 use crate::error::FlowError;
+use crate::partitioned::PartitionedFlowGuard;
 use crate::{FlowGuard, LimitStrategy};
 use futures_util::future::BoxFuture;
+use std::hash::Hash;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tower::{Layer, Service};
 
+/// Como o serviço se comporta quando não há permissão livre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AcquireMode {
+    /// Espera na fila do semáforo até uma permissão ser liberada (`FlowGuard::run`).
+    #[default]
+    Wait,
+    /// Rejeita na hora com `FlowError::Dropped` (`FlowGuard::try_run`).
+    Shed,
+}
+
 // --- 1. A LAYER ---
-pub struct FlowGuardLayer<S: LimitStrategy> {
+pub struct FlowGuardLayer<S: LimitStrategy, C = DefaultClassifier> {
     guard: Arc<FlowGuard<S>>,
+    mode: AcquireMode,
+    classifier: Arc<C>,
 }
 
 // Implementação manual de Clone para não exigir que S seja Clone
-impl<S: LimitStrategy> Clone for FlowGuardLayer<S> {
+impl<S: LimitStrategy, C> Clone for FlowGuardLayer<S, C> {
     fn clone(&self) -> Self {
         Self {
             guard: self.guard.clone(),
+            mode: self.mode,
+            classifier: self.classifier.clone(),
         }
     }
 }
 
 impl<S: LimitStrategy + 'static> FlowGuardLayer<S> {
     pub fn new(strategy: S) -> Self {
+        Self::from_guard(Arc::new(FlowGuard::new(strategy)))
+    }
+
+    /// Usa um `FlowGuard` já configurado (fila, timeout, ...) em vez de criar
+    /// um novo a partir da estratégia.
+    pub fn from_guard(guard: Arc<FlowGuard<S>>) -> Self {
         Self {
-            guard: Arc::new(FlowGuard::new(strategy)),
+            guard,
+            mode: AcquireMode::Wait,
+            classifier: Arc::new(DefaultClassifier),
         }
     }
 }
 
-impl<S, L> Layer<S> for FlowGuardLayer<L>
+impl<S: LimitStrategy + 'static, C> FlowGuardLayer<S, C> {
+    /// O `FlowGuard` compartilhado por todos os serviços desta layer, por
+    /// exemplo para chamar `close()`/`drain()` no desligamento gracioso.
+    pub fn guard(&self) -> &Arc<FlowGuard<S>> {
+        &self.guard
+    }
+
+    /// Define o modo de aquisição. Com `AcquireMode::Shed` o serviço responde
+    /// `FlowError::Dropped` assim que o limite é atingido.
+    pub fn with_mode(mut self, mode: AcquireMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Troca o classificador de resultados, por exemplo por
+    /// `HttpClassifier` para tratar 5xx/429 como sobrecarga e 4xx como neutro.
+    pub fn with_classifier<C2>(self, classifier: C2) -> FlowGuardLayer<S, C2> {
+        FlowGuardLayer {
+            guard: self.guard,
+            mode: self.mode,
+            classifier: Arc::new(classifier),
+        }
+    }
+}
+
+impl<S, L, C> Layer<S> for FlowGuardLayer<L, C>
 where
     L: LimitStrategy + 'static,
 {
-    type Service = FlowGuardService<S, L>;
+    type Service = FlowGuardService<S, L, C>;
 
     fn layer(&self, inner: S) -> Self::Service {
         FlowGuardService {
             inner,
             guard: self.guard.clone(),
+            mode: self.mode,
+            classifier: self.classifier.clone(),
         }
     }
 }
 
 // --- 2. O SERVICE ---
-pub struct FlowGuardService<S, L: LimitStrategy> {
+pub struct FlowGuardService<S, L: LimitStrategy, C = DefaultClassifier> {
     inner: S,
     guard: Arc<FlowGuard<L>>,
+    mode: AcquireMode,
+    classifier: Arc<C>,
 }
 
-impl<S: Clone, L: LimitStrategy> Clone for FlowGuardService<S, L> {
+impl<S: Clone, L: LimitStrategy, C> Clone for FlowGuardService<S, L, C> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
             guard: self.guard.clone(),
+            mode: self.mode,
+            classifier: self.classifier.clone(),
         }
     }
 }
 
-impl<S, L, Req> Service<Req> for FlowGuardService<S, L>
+impl<S, L, C, Req> Service<Req> for FlowGuardService<S, L, C>
 where
     S: Service<Req> + Clone + Send + 'static,
     S::Future: Send + 'static,
     L: LimitStrategy + 'static,
+    C: Classifier<S::Response, S::Error> + 'static,
     Req: Send + 'static,
 {
     type Response = S::Response;
@@ -79,10 +136,161 @@ where
     fn call(&mut self, req: Req) -> Self::Future {
         let mut inner = self.inner.clone();
         let guard = self.guard.clone();
+        let mode = self.mode;
+        let classifier = self.classifier.clone();
+
+        Box::pin(async move {
+            // O FlowGuard decide se executa, bloqueia ou descarta (Backpressure dinâmico)
+            match mode {
+                AcquireMode::Wait => guard.run_with(&*classifier, inner.call(req)).await,
+                AcquireMode::Shed => guard.try_run_with(&*classifier, inner.call(req)).await,
+            }
+        })
+    }
+}
+
+// --- 3. LAYER PARTICIONADA ---
+/// Layer que protege o serviço com um [`PartitionedFlowGuard`], extraindo a
+/// chave da partição de cada requisição com uma closure.
+///
+/// ```
+/// use flow_guard::integration::PartitionedFlowGuardLayer;
+/// use flow_guard::partitioned::PartitionedFlowGuard;
+/// use flow_guard::VegasStrategy;
+/// use std::sync::Arc;
+///
+/// let guard = Arc::new(
+///     PartitionedFlowGuard::new(VegasStrategy::new(100))
+///         .with_partition("premium".to_string(), 3.0)
+///         .with_partition("free".to_string(), 1.0),
+/// );
+///
+/// struct Request {
+///     tenant: String,
+/// }
+///
+/// let layer = PartitionedFlowGuardLayer::new(guard, |req: &Request| req.tenant.clone());
+/// # let _ = layer;
+/// ```
+pub struct PartitionedFlowGuardLayer<K, L: LimitStrategy, F, C = DefaultClassifier> {
+    guard: Arc<PartitionedFlowGuard<K, L>>,
+    key: Arc<F>,
+    mode: AcquireMode,
+    classifier: Arc<C>,
+}
+
+impl<K, L: LimitStrategy, F, C> Clone for PartitionedFlowGuardLayer<K, L, F, C> {
+    fn clone(&self) -> Self {
+        Self {
+            guard: self.guard.clone(),
+            key: self.key.clone(),
+            mode: self.mode,
+            classifier: self.classifier.clone(),
+        }
+    }
+}
+
+impl<K, L: LimitStrategy, F> PartitionedFlowGuardLayer<K, L, F> {
+    pub fn new(guard: Arc<PartitionedFlowGuard<K, L>>, key: F) -> Self {
+        Self {
+            guard,
+            key: Arc::new(key),
+            mode: AcquireMode::Wait,
+            classifier: Arc::new(DefaultClassifier),
+        }
+    }
+}
+
+impl<K, L: LimitStrategy, F, C> PartitionedFlowGuardLayer<K, L, F, C> {
+    pub fn guard(&self) -> &Arc<PartitionedFlowGuard<K, L>> {
+        &self.guard
+    }
+
+    /// Define o modo de aquisição (veja [`AcquireMode`]).
+    pub fn with_mode(mut self, mode: AcquireMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Troca o classificador de resultados.
+    pub fn with_classifier<C2>(self, classifier: C2) -> PartitionedFlowGuardLayer<K, L, F, C2> {
+        PartitionedFlowGuardLayer {
+            guard: self.guard,
+            key: self.key,
+            mode: self.mode,
+            classifier: Arc::new(classifier),
+        }
+    }
+}
+
+impl<S, K, L: LimitStrategy, F, C> Layer<S> for PartitionedFlowGuardLayer<K, L, F, C> {
+    type Service = PartitionedFlowGuardService<S, K, L, F, C>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PartitionedFlowGuardService {
+            inner,
+            guard: self.guard.clone(),
+            key: self.key.clone(),
+            mode: self.mode,
+            classifier: self.classifier.clone(),
+        }
+    }
+}
+
+pub struct PartitionedFlowGuardService<S, K, L: LimitStrategy, F, C = DefaultClassifier> {
+    inner: S,
+    guard: Arc<PartitionedFlowGuard<K, L>>,
+    key: Arc<F>,
+    mode: AcquireMode,
+    classifier: Arc<C>,
+}
+
+impl<S: Clone, K, L: LimitStrategy, F, C> Clone for PartitionedFlowGuardService<S, K, L, F, C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            guard: self.guard.clone(),
+            key: self.key.clone(),
+            mode: self.mode,
+            classifier: self.classifier.clone(),
+        }
+    }
+}
+
+impl<S, K, L, F, C, Req> Service<Req> for PartitionedFlowGuardService<S, K, L, F, C>
+where
+    S: Service<Req> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    K: Hash + Eq + Send + Sync + 'static,
+    L: LimitStrategy + 'static,
+    F: Fn(&Req) -> K + Send + Sync + 'static,
+    C: Classifier<S::Response, S::Error> + 'static,
+    Req: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = FlowError<S::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(FlowError::AppError)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let key = (self.key)(&req);
+        let mut inner = self.inner.clone();
+        let guard = self.guard.clone();
+        let mode = self.mode;
+        let classifier = self.classifier.clone();
 
         Box::pin(async move {
-            // O FlowGuard decide se executa ou bloqueia (Backpressure dinâmico)
-            guard.run(inner.call(req)).await
+            match mode {
+                AcquireMode::Wait => guard.run_with(&key, &*classifier, inner.call(req)).await,
+                AcquireMode::Shed => {
+                    guard
+                        .try_run_with(&key, &*classifier, inner.call(req))
+                        .await
+                }
+            }
         })
     }
 }
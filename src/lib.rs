@@ -10,26 +10,64 @@
 //! de backpressure dinâmico para proteger sistemas de alta carga.
 
 // 1. Declaração dos módulos internos
+pub mod classifier;
+mod codel;
+#[cfg(feature = "serde")]
+pub mod config;
+pub mod criticality;
+pub mod duration;
 pub mod error;
 pub mod limiter;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod partitioned;
+#[cfg(feature = "metrics-prometheus")]
+pub mod prometheus;
+mod rng;
 mod semaphore;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod stats;
 pub mod strategy;
+pub mod token;
+pub mod trace;
 
 #[cfg(feature = "tower")]
 pub mod integration;
 
-pub use error::FlowError;
+#[cfg(feature = "axum")]
+pub use classifier::HttpClassifier;
+pub use classifier::{Classifier, DefaultClassifier};
+#[cfg(feature = "serde")]
+pub use config::{FlowGuardConfig, StrategyConfig};
+pub use criticality::Criticality;
+pub use error::{ConfigError, FlowError, TraceError};
 pub use limiter::FlowGuard;
-pub use strategy::VegasStrategy;
+pub use partitioned::PartitionedFlowGuard;
+pub use semaphore::QueueDiscipline;
+pub use stats::LimitSnapshot;
+#[cfg(feature = "metrics-prometheus")]
+pub use stats::StatsSnapshot;
+pub use strategy::{
+    AimdStrategy, FixedStrategy, GradientStrategy, VegasStrategy, WindowedStrategy,
+};
+pub use token::{FlowToken, Outcome};
 
 #[cfg(feature = "tower")]
-pub use integration::FlowGuardLayer;
+pub use integration::{AcquireMode, FlowGuardLayer, PartitionedFlowGuardLayer};
 
 use std::time::Duration;
 
 /// Trait fundamental para definir como o limite de requisições deve se comportar.
 ///
-/// Implementado por estratégias como `VegasStrategy`.
+/// Implementado por estratégias como `VegasStrategy`, `AimdStrategy`,
+/// `GradientStrategy` e `FixedStrategy`.
+///
+/// O `FlowGuard` chama `on_success*`/`on_error` dentro de um span
+/// `limit_update` (nível DEBUG) e, se o limite mudar, emite um único evento
+/// `concurrency limit changed` nele. Para explicar o ajuste nesse evento, a
+/// estratégia pode preencher os campos `reason`, `base_rtt_us`, `queue`,
+/// `gradient` e `long_rtt_us` do span com `tracing::Span::current().record(...)`.
 pub trait LimitStrategy: Send + Sync {
     /// Retorna o limite de concorrência atual permitido pela estratégia.
     fn current_limit(&self) -> usize;
@@ -39,6 +77,32 @@ pub trait LimitStrategy: Send + Sync {
 
     /// Chamado quando ocorre um erro para que a estratégia possa reduzir a carga.
     fn on_error(&self);
+
+    /// Igual a `on_success`, informando também quantas execuções seguravam
+    /// permissão no momento da medição (incluindo esta).
+    ///
+    /// É o que o `FlowGuard` chama. Estratégias que precisam saber se o limite
+    /// está de fato em uso (ex.: para não crescer com pouca carga) sobrescrevem
+    /// este método; o padrão ignora `in_flight`.
+    fn on_success_with_in_flight(&self, latency: Duration, in_flight: usize) {
+        let _ = in_flight;
+        self.on_success(latency)
+    }
+}
+
+/// Estratégia que aceita novos parâmetros sem ser recriada (recarga a quente,
+/// feature `serde`). Exigida por `FlowGuard::update_config` e
+/// `FlowGuard::reload_from`.
+///
+/// Fica fora de [`LimitStrategy`] para que implementações externas não
+/// dependam das features habilitadas.
+#[cfg(feature = "serde")]
+pub trait ReloadableStrategy: LimitStrategy {
+    /// Aplica novos parâmetros.
+    ///
+    /// O limite atual é preservado, exceto quando fica fora dos novos
+    /// `min_limit`/`max_limit`.
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError>;
 }
 
 impl<S: LimitStrategy + ?Sized> LimitStrategy for std::sync::Arc<S> {
@@ -51,4 +115,37 @@ impl<S: LimitStrategy + ?Sized> LimitStrategy for std::sync::Arc<S> {
     fn on_error(&self) {
         (**self).on_error()
     }
+    fn on_success_with_in_flight(&self, latency: Duration, in_flight: usize) {
+        (**self).on_success_with_in_flight(latency, in_flight)
+    }
+}
+
+// Permite escolher a estratégia em tempo de execução (`FlowGuard<Box<dyn LimitStrategy>>`)
+impl<S: LimitStrategy + ?Sized> LimitStrategy for Box<S> {
+    fn current_limit(&self) -> usize {
+        (**self).current_limit()
+    }
+    fn on_success(&self, latency: std::time::Duration) {
+        (**self).on_success(latency)
+    }
+    fn on_error(&self) {
+        (**self).on_error()
+    }
+    fn on_success_with_in_flight(&self, latency: Duration, in_flight: usize) {
+        (**self).on_success_with_in_flight(latency, in_flight)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: ReloadableStrategy + ?Sized> ReloadableStrategy for std::sync::Arc<S> {
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        (**self).update_config(config)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: ReloadableStrategy + ?Sized> ReloadableStrategy for Box<S> {
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        (**self).update_config(config)
+    }
 }
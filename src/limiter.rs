@@ -1,58 +1,605 @@
 /* * Created and Developed by: Cleiton Augusto Correa Bezerra
  */
 
+use crate::classifier::{Classifier, DefaultClassifier};
+use crate::codel::CoDel;
+#[cfg(feature = "serde")]
+use crate::config::FlowGuardConfig;
+use crate::criticality::Criticality;
+#[cfg(feature = "serde")]
+use crate::error::ConfigError;
 use crate::error::FlowError;
+#[cfg(feature = "metrics")]
+use crate::metrics::{GuardMetrics, MetricsConfig};
+#[cfg(feature = "metrics-prometheus")]
+use crate::stats::{FlowStats, StatsSnapshot};
+use crate::stats::{LimitSnapshot, RttRing};
+use crate::token::{FlowToken, Outcome};
+use crate::trace::TraceRecorder;
 use crate::LimitStrategy;
+#[cfg(feature = "serde")]
+use crate::ReloadableStrategy;
+use std::convert::Infallible;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
 
-use crate::semaphore::DynamicSemaphore;
+use crate::semaphore::{AcquireError, DynamicPermit, DynamicSemaphore, QueueDiscipline};
 
-#[derive(Clone)]
 pub struct FlowGuard<S: LimitStrategy> {
+    name: Arc<str>,
     strategy: Arc<S>,
     semaphore: Arc<DynamicSemaphore>,
+    max_queue: Option<usize>,
+    queue_timeout: Option<Duration>,
+    codel: Option<Arc<CoDel>>,
+    default_outcome: Outcome,
+    thresholds: [f64; 4],
+    #[cfg(feature = "metrics-prometheus")]
+    stats: Arc<FlowStats>,
+    limits: Arc<watch::Sender<LimitSnapshot>>,
+    rtts: Arc<RttRing>,
+    run_span: bool,
+    recorder: Option<Arc<TraceRecorder>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<GuardMetrics>>,
+}
+
+// Implementação manual de Clone para não exigir que S seja Clone
+impl<S: LimitStrategy> Clone for FlowGuard<S> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            strategy: self.strategy.clone(),
+            semaphore: self.semaphore.clone(),
+            max_queue: self.max_queue,
+            queue_timeout: self.queue_timeout,
+            codel: self.codel.clone(),
+            default_outcome: self.default_outcome,
+            thresholds: self.thresholds,
+            #[cfg(feature = "metrics-prometheus")]
+            stats: self.stats.clone(),
+            limits: self.limits.clone(),
+            rtts: self.rtts.clone(),
+            run_span: self.run_span,
+            recorder: self.recorder.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
+        }
+    }
 }
 
 impl<S: LimitStrategy + 'static> FlowGuard<S> {
     pub fn new(strategy: S) -> Self {
         let initial_limit = strategy.current_limit();
         Self {
+            name: Arc::from("flow_guard"),
             strategy: Arc::new(strategy),
             semaphore: Arc::new(DynamicSemaphore::new(initial_limit)),
+            max_queue: None,
+            queue_timeout: None,
+            codel: None,
+            default_outcome: Outcome::Success,
+            thresholds: Criticality::ALL.map(Criticality::default_threshold),
+            #[cfg(feature = "metrics-prometheus")]
+            stats: Arc::default(),
+            limits: Arc::new(watch::Sender::new(LimitSnapshot {
+                limit: initial_limit,
+                ..LimitSnapshot::default()
+            })),
+            rtts: Arc::default(),
+            run_span: false,
+            recorder: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
+    /// Nome do guard, usado nos eventos e spans de `tracing` (padrão:
+    /// `flow_guard`). Útil para distinguir vários guards no mesmo processo.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Arc::from(name.into());
+        self
+    }
+
+    /// Envolve cada `run`/`try_run` em um span `flow_guard` (nível INFO) com
+    /// os campos `guard`, `queue_wait_us` e `exec_us`.
+    ///
+    /// O evento `concurrency limit changed` emitido ao fim da execução herda
+    /// o span.
+    pub fn with_tracing_span(mut self) -> Self {
+        self.run_span = true;
+        self
+    }
+
+    /// Grava cada medição (latência, resultado e execuções em andamento) em
+    /// `recorder`, para replay offline com `trace::replay`.
+    ///
+    /// O mesmo recorder pode ser compartilhado por vários guards.
+    pub fn with_trace_recorder(mut self, recorder: Arc<TraceRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Limita quantas execuções podem esperar por uma permissão ao mesmo tempo.
+    ///
+    /// Quem chega com a fila cheia recebe `FlowError::QueueFull`. Por padrão a
+    /// fila é ilimitada.
+    pub fn with_max_queue(mut self, max_queue: usize) -> Self {
+        self.max_queue = Some(max_queue);
+        self
+    }
+
+    /// Limita quanto tempo uma execução espera na fila por uma permissão.
+    ///
+    /// Ao estourar o prazo a execução recebe `FlowError::QueueTimeout`. Por
+    /// padrão a espera é ilimitada.
+    pub fn with_queue_timeout(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+
+    /// Liga o gerenciamento da fila por CoDel (Controlled Delay).
+    ///
+    /// O guard acompanha o menor tempo de espera na fila a cada `interval`.
+    /// Enquanto ele fica acima de `target` (fila que não esvazia), quem entra
+    /// na fila desiste após `target` com `FlowError::QueueTimeout`; fora disso
+    /// o timeout é `interval`. Valores comuns: `target` de 5ms e `interval` de
+    /// 100ms. Se [`with_queue_timeout`](Self::with_queue_timeout) também for
+    /// usado, vale o menor dos dois.
+    pub fn with_codel(mut self, target: Duration, interval: Duration) -> Self {
+        self.codel = Some(Arc::new(CoDel::new(target, interval)));
+        self
+    }
+
+    /// Ordem de atendimento da fila de espera dentro de cada nível de
+    /// criticidade (padrão: `QueueDiscipline::Fifo`).
+    pub fn with_queue_discipline(self, discipline: QueueDiscipline) -> Self {
+        self.semaphore.set_discipline(discipline);
+        self
+    }
+
+    /// Resultado registrado quando um [`FlowToken`] é descartado sem que
+    /// `success()`, `dropped()` ou `ignore()` tenha sido chamado.
+    ///
+    /// O padrão é `Outcome::Success`.
+    pub fn with_default_outcome(mut self, outcome: Outcome) -> Self {
+        self.default_outcome = outcome;
+        self
+    }
+
+    /// Fração do limite a partir da qual execuções de `criticality` são
+    /// rejeitadas na hora com `FlowError::Dropped`, sem entrar na fila.
+    ///
+    /// Com 1.0 o nível só espera na fila como de costume. Por padrão só
+    /// `Criticality::Sheddable` tem limiar (0.8).
+    ///
+    /// # Panics
+    ///
+    /// Se `threshold` não estiver em `(0, 1]`.
+    pub fn with_criticality_threshold(mut self, criticality: Criticality, threshold: f64) -> Self {
+        assert!(
+            threshold > 0.0 && threshold <= 1.0,
+            "criticality threshold must be in (0, 1]"
+        );
+        self.thresholds[criticality.rank()] = threshold;
+        self
+    }
+
+    /// Emite as métricas deste guard pela fachada do crate `metrics`.
+    ///
+    /// As métricas são registradas no recorder instalado no momento da
+    /// chamada, então instale o recorder antes de construir o guard. Os
+    /// gauges são atualizados a cada aquisição e liberação de permissão.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, config: MetricsConfig) -> Self {
+        let metrics = GuardMetrics::register(&config);
+        metrics.gauges(self.current_limit(), self.in_flight(), self.queue_len());
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
     pub async fn run<F, T, E>(&self, f: F) -> Result<T, FlowError<E>>
     where
         F: std::future::Future<Output = Result<T, E>>,
     {
-        // 1. Tenta adquirir permissão (Backpressure dinâmico)
-        let _permit = self
-            .semaphore
-            .acquire()
+        self.run_with(&DefaultClassifier, f).await
+    }
+
+    /// Igual a [`run`](Self::run), mas usa `classifier` para decidir se o
+    /// resultado conta como sucesso, sobrecarga ou deve ser ignorado.
+    pub async fn run_with<C, F, T, E>(&self, classifier: &C, f: F) -> Result<T, FlowError<E>>
+    where
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.run_prioritized(Criticality::Normal, classifier, f)
+            .await
+    }
+
+    /// Igual a [`run`](Self::run), com a criticidade definindo a posição na
+    /// fila e o limiar de utilização (veja
+    /// [`with_criticality_threshold`](Self::with_criticality_threshold)).
+    pub async fn run_with_criticality<F, T, E>(
+        &self,
+        criticality: Criticality,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.run_prioritized(criticality, &DefaultClassifier, f)
+            .await
+    }
+
+    async fn run_prioritized<C, F, T, E>(
+        &self,
+        criticality: Criticality,
+        classifier: &C,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        let span = self.run_span();
+        async {
+            // 1. Tenta adquirir permissão (Backpressure dinâmico)
+            let wait = Instant::now();
+            let permit = self
+                .acquire_permit(criticality)
+                .await
+                .map_err(acquire_error)?;
+            span.record("queue_wait_us", wait.elapsed().as_micros() as u64);
+
+            self.execute(permit, &span, classifier, f).await
+        }
+        .instrument(span.clone())
+        .await
+    }
+
+    /// Versão não-bloqueante de [`run`](Self::run).
+    ///
+    /// Se não houver permissão livre no momento, retorna
+    /// `FlowError::Dropped` imediatamente em vez de entrar na fila de espera,
+    /// permitindo responder 503 sem acumular latência.
+    pub async fn try_run<F, T, E>(&self, f: F) -> Result<T, FlowError<E>>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.try_run_with(&DefaultClassifier, f).await
+    }
+
+    /// Versão não-bloqueante de [`run_with`](Self::run_with).
+    pub async fn try_run_with<C, F, T, E>(&self, classifier: &C, f: F) -> Result<T, FlowError<E>>
+    where
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        let span = self.run_span();
+        async {
+            let permit = self
+                .try_acquire_permit(Criticality::Normal)
+                .map_err(acquire_error)?;
+            span.record("queue_wait_us", 0u64);
+
+            self.execute(permit, &span, classifier, f).await
+        }
+        .instrument(span.clone())
+        .await
+    }
+
+    pub(crate) fn run_span(&self) -> Span {
+        if !self.run_span {
+            return Span::none();
+        }
+        tracing::info_span!(
+            "flow_guard",
+            guard = %self.name,
+            queue_wait_us = tracing::field::Empty,
+            exec_us = tracing::field::Empty,
+        )
+    }
+
+    /// Espera por uma permissão e devolve um [`FlowToken`] que a segura.
+    ///
+    /// Útil quando o trabalho protegido não cabe em uma única future (respostas
+    /// em streaming, handlers com várias etapas, código síncrono). O resultado
+    /// é informado com `token.success()`, `token.dropped()` ou `token.ignore()`.
+    pub async fn acquire(&self) -> Result<FlowToken<S>, FlowError<Infallible>> {
+        self.acquire_with_criticality(Criticality::Normal).await
+    }
+
+    /// Igual a [`acquire`](Self::acquire), com a criticidade definindo a
+    /// posição na fila e o limiar de utilização.
+    pub async fn acquire_with_criticality(
+        &self,
+        criticality: Criticality,
+    ) -> Result<FlowToken<S>, FlowError<Infallible>> {
+        let permit = self
+            .acquire_permit(criticality)
             .await
-            .map_err(|_| FlowError::Dropped)?;
+            .map_err(acquire_error)?;
+
+        Ok(FlowToken::new(self.clone(), permit))
+    }
+
+    /// Versão não-bloqueante de [`acquire`](Self::acquire): retorna
+    /// `FlowError::Dropped` se não houver permissão livre.
+    pub fn try_acquire(&self) -> Result<FlowToken<S>, FlowError<Infallible>> {
+        let permit = self
+            .try_acquire_permit(Criticality::Normal)
+            .map_err(acquire_error)?;
+
+        Ok(FlowToken::new(self.clone(), permit))
+    }
+
+    pub(crate) async fn acquire_permit(
+        &self,
+        criticality: Criticality,
+    ) -> Result<DynamicPermit, AcquireError> {
+        self.publish_gauges();
+        if self.over_threshold(criticality) {
+            let result = Err(AcquireError::NoPermits);
+            self.count_acquire(&result);
+            return result;
+        }
+
+        let timeout = match &self.codel {
+            Some(codel) => {
+                let codel_timeout = codel.queue_timeout();
+                Some(
+                    self.queue_timeout
+                        .map_or(codel_timeout, |t| t.min(codel_timeout)),
+                )
+            }
+            None => self.queue_timeout,
+        };
 
+        let wait = Instant::now();
+        let result = self
+            .semaphore
+            .acquire(criticality, self.max_queue, timeout)
+            .await;
+
+        if let Some(codel) = &self.codel {
+            if matches!(result, Ok(_) | Err(AcquireError::Timeout)) {
+                codel.record_sojourn(wait.elapsed());
+            }
+        }
+        self.count_acquire(&result);
+        result
+    }
+
+    pub(crate) fn try_acquire_permit(
+        &self,
+        criticality: Criticality,
+    ) -> Result<DynamicPermit, AcquireError> {
+        let result = if self.over_threshold(criticality) {
+            Err(AcquireError::NoPermits)
+        } else {
+            self.semaphore.try_acquire()
+        };
+        self.count_acquire(&result);
+        result
+    }
+
+    /// Admite uma execução acima do limite (cota garantida de uma partição),
+    /// contando-a nas estatísticas como uma aquisição comum.
+    pub(crate) fn acquire_over_limit(&self) -> Result<DynamicPermit, AcquireError> {
+        let result = self.semaphore.acquire_over_limit();
+        self.count_acquire(&result);
+        result
+    }
+
+    /// Se a utilização já passou do limiar configurado para `criticality`.
+    fn over_threshold(&self, criticality: Criticality) -> bool {
+        let threshold = self.thresholds[criticality.rank()];
+        threshold < 1.0
+            && self.semaphore.in_flight() as f64
+                >= threshold * self.semaphore.current_limit() as f64
+    }
+
+    #[cfg_attr(
+        not(any(feature = "metrics", feature = "metrics-prometheus")),
+        allow(unused_variables)
+    )]
+    fn count_acquire(&self, result: &Result<DynamicPermit, AcquireError>) {
+        #[cfg(feature = "metrics-prometheus")]
+        match result {
+            Ok(_) => self.stats.acquired(),
+            Err(AcquireError::NoPermits) => self.stats.dropped(),
+            Err(AcquireError::QueueFull | AcquireError::Closed) => self.stats.rejected(),
+            Err(AcquireError::Timeout) => self.stats.timed_out(),
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.acquire(result);
+        }
+        self.publish_gauges();
+    }
+
+    /// Atualiza os gauges de limite, permissões em uso e fila.
+    pub(crate) fn publish_gauges(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.gauges(self.current_limit(), self.in_flight(), self.queue_len());
+        }
+    }
+
+    pub(crate) async fn execute<C, F, T, E>(
+        &self,
+        permit: DynamicPermit,
+        span: &Span,
+        classifier: &C,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
         let start = Instant::now();
 
         // 2. Executa a tarefa do usuário
         let result = f.await;
 
         let duration = start.elapsed();
+        span.record("exec_us", duration.as_micros() as u64);
 
         // 3. Informa a estratégia sobre o sucesso ou falha
-        match &result {
-            Ok(_) => self.strategy.on_success(duration),
-            Err(_) => self.strategy.on_error(),
+        self.record(classifier.classify(&result), duration);
+        // Libera a permissão antes de publicar os gauges, para que reflitam a saída
+        drop(permit);
+        self.publish_gauges();
+
+        // 4. Retorna o resultado
+        result.map_err(FlowError::AppError)
+    }
+
+    /// Repassa uma medição para a estratégia e aplica o novo limite ao semáforo.
+    pub(crate) fn record(&self, outcome: Outcome, latency: Duration) {
+        #[cfg(feature = "metrics-prometheus")]
+        self.stats.latency(latency);
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.latency(latency);
+        }
+        // A permissão desta execução ainda está em uso, então entra na conta
+        let in_flight = self.semaphore.in_flight();
+        if let Some(recorder) = &self.recorder {
+            recorder.record(latency, outcome, in_flight);
         }
 
-        // 4. ATUALIZAÇÃO CRÍTICA: Atualiza o semáforo com o novo limite
+        // A estratégia roda dentro deste span e pode registrar nele o motivo
+        // do ajuste (veja `LimitStrategy`), que sai no único evento abaixo
+        let span = tracing::debug_span!(
+            "limit_update",
+            guard = %self.name,
+            reason = Empty,
+            base_rtt_us = Empty,
+            queue = Empty,
+            gradient = Empty,
+            long_rtt_us = Empty,
+        );
+        let outcome_name = span.in_scope(|| match outcome {
+            Outcome::Success => {
+                self.strategy.on_success_with_in_flight(latency, in_flight);
+                Some("success")
+            }
+            Outcome::Dropped => {
+                self.strategy.on_error();
+                Some("dropped")
+            }
+            Outcome::Ignore => None,
+        });
+        let Some(outcome_name) = outcome_name else {
+            return;
+        };
+
+        // ATUALIZAÇÃO CRÍTICA: Atualiza o semáforo com o novo limite
+        // (só escreve quando muda, para não disputar o cache à toa)
         let new_limit = self.strategy.current_limit();
-        self.semaphore.set_limit(new_limit);
+        let old_limit = self.semaphore.current_limit();
+        let changed = new_limit != old_limit;
+        if changed {
+            self.semaphore.set_limit(new_limit);
+            span.in_scope(|| {
+                tracing::debug!(
+                    old_limit,
+                    new_limit,
+                    outcome = outcome_name,
+                    rtt_us = latency.as_micros() as u64,
+                    in_flight,
+                    "concurrency limit changed"
+                )
+            });
+        }
 
-        // 5. Retorna o resultado
-        result.map_err(FlowError::AppError)
+        let rtt = (outcome == Outcome::Success).then_some(latency);
+        self.publish_limit(changed, rtt);
+    }
+
+    /// Guarda a amostra de RTT e notifica os assinantes se o limite mudou.
+    fn publish_limit(&self, changed: bool, rtt: Option<Duration>) {
+        // Sem assinantes não há o que guardar nem notificar
+        if self.limits.receiver_count() == 0 {
+            return;
+        }
+
+        // A amostra vai para o anel sem lock; o lock do canal só é tomado
+        // quando há mudança para publicar
+        if let Some(rtt) = rtt {
+            self.rtts.push(rtt);
+        }
+        if changed {
+            self.limits
+                .send_modify(|snapshot| self.fill_snapshot(snapshot));
+        }
+    }
+
+    fn fill_snapshot(&self, snapshot: &mut LimitSnapshot) {
+        snapshot.limit = self.semaphore.current_limit();
+        snapshot.in_flight = self.semaphore.in_flight();
+        snapshot.queue_len = self.semaphore.queue_len();
+        self.rtts.copy_to(&mut snapshot.recent_rtts);
+    }
+
+    /// Recebe um [`LimitSnapshot`] novo sempre que o limite do semáforo muda.
+    ///
+    /// O valor inicial do receiver reflete o estado no momento da assinatura.
+    /// As amostras de RTT só são guardadas enquanto houver algum assinante.
+    ///
+    /// ```no_run
+    /// # async fn demo(guard: flow_guard::FlowGuard<flow_guard::VegasStrategy>) {
+    /// let mut limits = guard.subscribe();
+    /// while limits.changed().await.is_ok() {
+    ///     let snapshot = limits.borrow_and_update().clone();
+    ///     println!("novo limite: {}", snapshot.limit);
+    /// }
+    /// # }
+    /// ```
+    pub fn subscribe(&self) -> watch::Receiver<LimitSnapshot> {
+        let receiver = self.limits.subscribe();
+        // Atualiza sem notificar: a assinatura nova não conta como mudança
+        self.limits.send_if_modified(|snapshot| {
+            self.fill_snapshot(snapshot);
+            false
+        });
+        receiver
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn default_outcome(&self) -> Outcome {
+        self.default_outcome
+    }
+
+    /// Fecha o guard para desligamento gracioso.
+    ///
+    /// Novas execuções falham com `FlowError::Closed` e todas as que estão na
+    /// fila de espera são acordadas com o mesmo erro. Execuções que já têm
+    /// permissão continuam até o fim; use [`drain`](Self::drain) para esperar
+    /// por elas.
+    pub fn close(&self) {
+        self.semaphore.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.semaphore.is_closed()
+    }
+
+    /// Resolve quando todas as permissões em uso forem liberadas.
+    ///
+    /// Normalmente chamado depois de [`close`](Self::close), para que nenhuma
+    /// execução nova entre enquanto o desligamento espera.
+    pub async fn drain(&self) {
+        self.semaphore.drain().await
     }
 
     // Métodos para observabilidade
@@ -63,4 +610,132 @@ impl<S: LimitStrategy + 'static> FlowGuard<S> {
     pub fn available_permits(&self) -> usize {
         self.semaphore.available_permits()
     }
+
+    /// Número de execuções segurando uma permissão neste momento.
+    ///
+    /// Logo após uma redução do limite pode ficar acima de `current_limit()`;
+    /// novas execuções esperam até que ele volte para baixo do limite.
+    pub fn in_flight(&self) -> usize {
+        self.semaphore.in_flight()
+    }
+
+    /// Se o CoDel considera a fila sobrecarregada (timeout curto em vigor).
+    /// Sempre `false` sem [`with_codel`](Self::with_codel).
+    pub fn is_overloaded(&self) -> bool {
+        self.codel
+            .as_ref()
+            .is_some_and(|codel| codel.is_overloaded())
+    }
+
+    /// Número de execuções esperando por uma permissão.
+    pub fn queue_len(&self) -> usize {
+        self.semaphore.queue_len()
+    }
+
+    /// Contadores acumulados e histograma de latência deste guard (feature
+    /// `metrics-prometheus`).
+    #[cfg(feature = "metrics-prometheus")]
+    pub fn stats(&self) -> StatsSnapshot {
+        self.stats.snapshot()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: ReloadableStrategy + 'static> FlowGuard<S> {
+    /// Recarrega a quente a configuração de um guard em uso.
+    ///
+    /// Aplica os parâmetros da estratégia (`ReloadableStrategy::update_config`) e
+    /// `queue_discipline`. O novo limite vale na hora, inclusive para quem
+    /// está na fila. Os demais campos (`name`, `max_queue`, `queue_timeout`,
+    /// `codel`, limiares e `tracing_span`) só valem na construção.
+    ///
+    /// Em caso de erro nada é alterado.
+    pub fn update_config(&self, config: &FlowGuardConfig) -> Result<(), ConfigError> {
+        self.strategy.update_config(&config.strategy)?;
+        self.semaphore.set_discipline(config.queue_discipline);
+
+        let old_limit = self.semaphore.current_limit();
+        let new_limit = self.strategy.current_limit();
+        let changed = new_limit != old_limit;
+        if changed {
+            self.semaphore.set_limit(new_limit);
+        }
+        tracing::info!(
+            guard = %self.name,
+            strategy = config.strategy.kind(),
+            old_limit,
+            new_limit,
+            "configuration reloaded"
+        );
+
+        self.publish_limit(changed, None);
+        self.publish_gauges();
+        Ok(())
+    }
+
+    /// Aplica com [`update_config`](Self::update_config) cada configuração
+    /// publicada em `configs`, até o sender ser descartado ou o guard ser
+    /// fechado com [`close`](Self::close).
+    ///
+    /// O valor atual do receiver é tratado como já aplicado. Configurações
+    /// rejeitadas geram `tracing::warn!` e a anterior continua valendo. Vários
+    /// guards podem acompanhar o mesmo canal com clones do receiver. Precisa
+    /// ser chamado dentro de um runtime tokio.
+    ///
+    /// A tarefa segura um clone do guard e o receiver. Com
+    /// [`watch_file`](crate::config::watch_file), que só para quando todos os
+    /// receivers são descartados, nenhum dos dois lados termina sozinho: ao
+    /// descartar o guard sem fechá-lo, aborte o `JoinHandle` retornado.
+    pub fn reload_from(
+        &self,
+        mut configs: watch::Receiver<FlowGuardConfig>,
+    ) -> tokio::task::JoinHandle<()> {
+        let guard = self.clone();
+        // Lido antes do spawn, para não perder o que for publicado em seguida
+        let mut current = configs.borrow_and_update().clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    changed = configs.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                    _ = guard.semaphore.closed() => break,
+                }
+
+                let config = configs.borrow_and_update().clone();
+                if let Err(err) = guard.update_config(&config) {
+                    tracing::warn!(
+                        guard = %guard.name,
+                        error = %err,
+                        "rejected configuration, keeping the previous one"
+                    );
+                    continue;
+                }
+
+                let fixed_fields_changed = FlowGuardConfig {
+                    strategy: current.strategy.clone(),
+                    queue_discipline: current.queue_discipline,
+                    ..config.clone()
+                } != current;
+                if fixed_fields_changed {
+                    tracing::warn!(
+                        guard = %guard.name,
+                        "only strategy and queue_discipline are reloaded, rebuild the guard to apply the other changes"
+                    );
+                }
+                current = config;
+            }
+        })
+    }
+}
+
+pub(crate) fn acquire_error<E>(err: AcquireError) -> FlowError<E> {
+    match err {
+        AcquireError::NoPermits => FlowError::Dropped,
+        AcquireError::Closed => FlowError::Closed,
+        AcquireError::QueueFull => FlowError::QueueFull,
+        AcquireError::Timeout => FlowError::QueueTimeout,
+    }
 }
@@ -0,0 +1,144 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Integração com a fachada do crate `metrics`
+ */
+
+use crate::semaphore::AcquireError;
+use ::metrics::{Counter, Gauge, Histogram, Label};
+use std::time::Duration;
+
+/// Nomes e rótulos das métricas que um `FlowGuard` emite pela fachada do
+/// crate `metrics` (`FlowGuard::with_metrics`).
+///
+/// Com o prefixo padrão `flowguard` são emitidos:
+///
+/// - gauges `flowguard_limit`, `flowguard_in_flight` e `flowguard_queue_depth`;
+/// - counters `flowguard_acquired_total`, `flowguard_dropped_total`,
+///   `flowguard_rejected_total` e `flowguard_timed_out_total`;
+/// - histograma `flowguard_latency_seconds`.
+///
+/// Os rótulos são anexados a todas elas, o que permite distinguir vários
+/// guards no mesmo processo:
+///
+/// ```
+/// use flow_guard::metrics::MetricsConfig;
+///
+/// let db = MetricsConfig::new().with_label("guard", "db");
+/// let cache = MetricsConfig::with_prefix("cache_guard").with_label("region", "us-east");
+/// # let _ = (db, cache);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    prefix: String,
+    labels: Vec<Label>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self::with_prefix("flowguard")
+    }
+}
+
+impl MetricsConfig {
+    /// Configuração com o prefixo `flowguard` e sem rótulos.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configuração com outro prefixo para os nomes das métricas.
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Acrescenta um rótulo fixo a todas as métricas do guard.
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.push(Label::new(key.into(), value.into()));
+        self
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    fn name(&self, metric: &str) -> String {
+        format!("{}_{metric}", self.prefix)
+    }
+}
+
+/// Handles já registrados no recorder; emitir uma medição não aloca.
+pub(crate) struct GuardMetrics {
+    limit: Gauge,
+    in_flight: Gauge,
+    queue_depth: Gauge,
+    acquired: Counter,
+    dropped: Counter,
+    rejected: Counter,
+    timed_out: Counter,
+    latency: Histogram,
+}
+
+impl GuardMetrics {
+    /// Registra as métricas no recorder instalado neste momento.
+    pub(crate) fn register(config: &MetricsConfig) -> Self {
+        let labels = config.labels.clone();
+        let gauge = |metric: &str, help: &'static str| {
+            let name = config.name(metric);
+            ::metrics::describe_gauge!(name.clone(), help);
+            ::metrics::gauge!(name, labels.clone())
+        };
+        let counter = |metric: &str, help: &'static str| {
+            let name = config.name(metric);
+            ::metrics::describe_counter!(name.clone(), help);
+            ::metrics::counter!(name, labels.clone())
+        };
+
+        let latency_name = config.name("latency_seconds");
+        ::metrics::describe_histogram!(
+            latency_name.clone(),
+            ::metrics::Unit::Seconds,
+            "Latency of completed executions."
+        );
+
+        Self {
+            limit: gauge("limit", "Current concurrency limit."),
+            in_flight: gauge("in_flight", "Executions currently holding a permit."),
+            queue_depth: gauge("queue_depth", "Executions waiting for a permit."),
+            acquired: counter("acquired_total", "Permits granted."),
+            dropped: counter("dropped_total", "Executions shed without waiting."),
+            rejected: counter(
+                "rejected_total",
+                "Executions rejected because the queue was full or the guard was closed.",
+            ),
+            timed_out: counter(
+                "timed_out_total",
+                "Executions that gave up after the maximum queue wait.",
+            ),
+            latency: ::metrics::histogram!(latency_name, labels.clone()),
+        }
+    }
+
+    pub(crate) fn acquire<T>(&self, result: &Result<T, AcquireError>) {
+        match result {
+            Ok(_) => self.acquired.increment(1),
+            Err(AcquireError::NoPermits) => self.dropped.increment(1),
+            Err(AcquireError::QueueFull | AcquireError::Closed) => self.rejected.increment(1),
+            Err(AcquireError::Timeout) => self.timed_out.increment(1),
+        }
+    }
+
+    pub(crate) fn latency(&self, latency: Duration) {
+        self.latency.record(latency.as_secs_f64());
+    }
+
+    pub(crate) fn gauges(&self, limit: usize, in_flight: usize, queue_depth: usize) {
+        self.limit.set(limit as f64);
+        self.in_flight.set(in_flight as f64);
+        self.queue_depth.set(queue_depth as f64);
+    }
+}
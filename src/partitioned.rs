@@ -0,0 +1,461 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Limite particionado por chave (multi-tenant)
+ */
+
+use crate::classifier::{Classifier, DefaultClassifier};
+use crate::criticality::Criticality;
+use crate::error::FlowError;
+use crate::limiter::{acquire_error, FlowGuard};
+use crate::semaphore::{AcquireError, DynamicPermit, DynamicSemaphore};
+use crate::LimitStrategy;
+use parking_lot::Mutex;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::Instrument;
+
+struct Partition {
+    weight: f64,
+    /// Ordem de registro, para desempatar a distribuição do resto.
+    index: usize,
+    /// Vagas da cota garantida; o limite acompanha a cota atual.
+    quota: Arc<DynamicSemaphore>,
+    /// Execuções da partição, pela cota ou com folga emprestada.
+    in_flight: AtomicUsize,
+}
+
+impl Partition {
+    fn new(weight: f64, index: usize) -> Self {
+        assert!(
+            weight.is_finite() && weight >= 0.0,
+            "partition weight must be a finite, non-negative number"
+        );
+        Self {
+            weight,
+            index,
+            quota: Arc::new(DynamicSemaphore::new(0)),
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Divide o limite adaptativo global entre partições (tenant, rota, chave de
+/// API), para que uma chave barulhenta não tome todas as permissões.
+///
+/// A estratégia continua decidindo o limite global `L`. Cada partição tem um
+/// peso e, com ele, uma cota garantida de `L * peso / soma dos pesos`
+/// arredondada para baixo; o resto vai, uma vaga por vez, para as partições
+/// de maior peso (em empate, para a registrada primeiro). As cotas somam no
+/// máximo `L`: com mais partições que vagas, algumas ficam sem cota e só usam
+/// folga emprestada. Uma execução é admitida:
+///
+/// - pela cota, se a partição ainda tem vaga nela, mesmo com o limite global
+///   esgotado por empréstimos de outras partições (o total pode passar de `L`
+///   até esses empréstimos terminarem); ou
+/// - com folga emprestada, se o total em execução está abaixo de `L`.
+///
+/// Quem não consegue nenhuma das duas espera ao mesmo tempo na fila da cota
+/// da sua partição e na fila global, e entra pela primeira que liberar uma
+/// vaga. Cada fila entrega a vaga diretamente a um único waiter, na ordem de
+/// [`Criticality`] e da disciplina configurada.
+///
+/// A parte global é um [`FlowGuard`] comum: fila máxima, timeout de fila,
+/// CoDel, limiares de criticidade, métricas, `subscribe()` e `stats()` valem
+/// para o guard passado em [`from_guard`](Self::from_guard) e ficam acessíveis
+/// por [`guard`](Self::guard).
+///
+/// Chaves não registradas caem na partição padrão, cujo peso é 0 (só usa
+/// capacidade emprestada) a menos que [`with_default_weight`](Self::with_default_weight)
+/// diga outra coisa.
+///
+/// ```
+/// use flow_guard::partitioned::PartitionedFlowGuard;
+/// use flow_guard::VegasStrategy;
+///
+/// let guard = PartitionedFlowGuard::new(VegasStrategy::new(100))
+///     .with_partition("premium", 3.0)
+///     .with_partition("free", 1.0);
+///
+/// assert_eq!(guard.share("premium"), 75);
+/// assert_eq!(guard.share("free"), 25);
+/// assert_eq!(guard.share("unknown"), 0);
+/// ```
+pub struct PartitionedFlowGuard<K, S: LimitStrategy> {
+    guard: FlowGuard<S>,
+    partitions: HashMap<K, Partition>,
+    default_partition: Partition,
+    total_weight: f64,
+    /// Limite global para o qual as cotas foram dimensionadas por último
+    /// (`usize::MAX` força o redimensionamento). O lock serializa o
+    /// redimensionamento, para que ele não termine com um limite antigo.
+    sized_for: Mutex<usize>,
+}
+
+impl<K: Hash + Eq, S: LimitStrategy + 'static> PartitionedFlowGuard<K, S> {
+    pub fn new(strategy: S) -> Self {
+        Self::from_guard(FlowGuard::new(strategy))
+    }
+
+    /// Particiona um guard já configurado (nome, fila, timeout, métricas...).
+    pub fn from_guard(guard: FlowGuard<S>) -> Self {
+        Self {
+            guard,
+            partitions: HashMap::new(),
+            default_partition: Partition::new(0.0, usize::MAX),
+            total_weight: 0.0,
+            sized_for: Mutex::new(usize::MAX),
+        }
+    }
+
+    /// Registra uma partição com o peso dado.
+    ///
+    /// # Panics
+    ///
+    /// Se `weight` for negativo ou não finito.
+    pub fn with_partition(mut self, key: K, weight: f64) -> Self {
+        let index = self
+            .partitions
+            .get(&key)
+            .map_or(self.partitions.len(), |old| old.index);
+        if let Some(old) = self.partitions.insert(key, Partition::new(weight, index)) {
+            self.total_weight -= old.weight;
+        }
+        self.total_weight += weight;
+        *self.sized_for.get_mut() = usize::MAX;
+        self
+    }
+
+    /// Peso da partição que recebe as chaves não registradas (padrão: 0).
+    ///
+    /// # Panics
+    ///
+    /// Se `weight` for negativo ou não finito.
+    pub fn with_default_weight(mut self, weight: f64) -> Self {
+        self.total_weight += weight - self.default_partition.weight;
+        self.default_partition = Partition::new(weight, usize::MAX);
+        *self.sized_for.get_mut() = usize::MAX;
+        self
+    }
+
+    pub async fn run<Q, F, T, E>(&self, key: &Q, f: F) -> Result<T, FlowError<E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.run_prioritized(key, Criticality::Normal, &DefaultClassifier, f)
+            .await
+    }
+
+    /// Igual a [`run`](Self::run), usando `classifier` para interpretar o
+    /// resultado.
+    pub async fn run_with<Q, C, F, T, E>(
+        &self,
+        key: &Q,
+        classifier: &C,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.run_prioritized(key, Criticality::Normal, classifier, f)
+            .await
+    }
+
+    /// Igual a [`run`](Self::run), com a criticidade definindo a posição nas
+    /// filas e o limiar de utilização para emprestar folga.
+    pub async fn run_with_criticality<Q, F, T, E>(
+        &self,
+        key: &Q,
+        criticality: Criticality,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.run_prioritized(key, criticality, &DefaultClassifier, f)
+            .await
+    }
+
+    async fn run_prioritized<Q, C, F, T, E>(
+        &self,
+        key: &Q,
+        criticality: Criticality,
+        classifier: &C,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        let partition = self.partition(key);
+        let span = self.guard.run_span();
+        async {
+            let wait = Instant::now();
+            let (permit, global) = self
+                .admit(partition, criticality)
+                .await
+                .map_err(acquire_error)?;
+            span.record("queue_wait_us", wait.elapsed().as_micros() as u64);
+
+            self.execute(permit, global, &span, classifier, f).await
+        }
+        .instrument(span.clone())
+        .await
+    }
+
+    /// Versão não-bloqueante de [`run`](Self::run): sem cota nem folga
+    /// global, retorna `FlowError::Dropped` imediatamente.
+    pub async fn try_run<Q, F, T, E>(&self, key: &Q, f: F) -> Result<T, FlowError<E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        self.try_run_with(key, &DefaultClassifier, f).await
+    }
+
+    /// Versão não-bloqueante de [`run_with`](Self::run_with).
+    pub async fn try_run_with<Q, C, F, T, E>(
+        &self,
+        key: &Q,
+        classifier: &C,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        let partition = self.partition(key);
+        let span = self.guard.run_span();
+        async {
+            let (permit, global) = self.try_admit(partition).map_err(acquire_error)?;
+            span.record("queue_wait_us", 0u64);
+
+            self.execute(permit, global, &span, classifier, f).await
+        }
+        .instrument(span.clone())
+        .await
+    }
+
+    fn partition<Q>(&self, key: &Q) -> &Partition
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.partitions.get(key).unwrap_or(&self.default_partition)
+    }
+
+    fn try_admit<'a>(
+        &'a self,
+        partition: &'a Partition,
+    ) -> Result<(PartitionPermit<'a>, DynamicPermit), AcquireError> {
+        self.resize_quotas();
+        if let Ok(quota) = partition.quota.try_acquire() {
+            return self.admit_quota(partition, quota);
+        }
+
+        let global = self.guard.try_acquire_permit(Criticality::Normal)?;
+        Ok((PartitionPermit::new(partition, None), global))
+    }
+
+    async fn admit<'a>(
+        &'a self,
+        partition: &'a Partition,
+        criticality: Criticality,
+    ) -> Result<(PartitionPermit<'a>, DynamicPermit), AcquireError> {
+        self.resize_quotas();
+        if let Ok(quota) = partition.quota.try_acquire() {
+            return self.admit_quota(partition, quota);
+        }
+
+        // A fila global aplica fila máxima, timeout e fechamento; a da cota só
+        // corre junto e é cancelada quando a global termina. Uma vaga que já
+        // tinha sido entregue à espera cancelada volta pelo `Drop` e segue
+        // para o próximo da fila.
+        tokio::select! {
+            biased;
+            Ok(quota) = partition.quota.acquire(criticality, None, None) => {
+                self.admit_quota(partition, quota)
+            }
+            global = self.guard.acquire_permit(criticality) => {
+                Ok((PartitionPermit::new(partition, None), global?))
+            }
+        }
+    }
+
+    /// Admite pela cota: a execução conta no total mesmo acima do limite.
+    fn admit_quota<'a>(
+        &'a self,
+        partition: &'a Partition,
+        quota: DynamicPermit,
+    ) -> Result<(PartitionPermit<'a>, DynamicPermit), AcquireError> {
+        let global = self.guard.acquire_over_limit()?;
+        Ok((PartitionPermit::new(partition, Some(quota)), global))
+    }
+
+    async fn execute<C, F, T, E>(
+        &self,
+        permit: PartitionPermit<'_>,
+        global: DynamicPermit,
+        span: &tracing::Span,
+        classifier: &C,
+        f: F,
+    ) -> Result<T, FlowError<E>>
+    where
+        C: Classifier<T, E> + ?Sized,
+        F: std::future::Future<Output = Result<T, E>>,
+    {
+        let result = self.guard.execute(global, span, classifier, f).await;
+
+        // Ajusta as cotas ao limite novo antes de liberar a vaga da partição
+        self.resize_quotas();
+        drop(permit);
+        result
+    }
+
+    /// Redimensiona as cotas se o limite global mudou desde a última vez.
+    fn resize_quotas(&self) {
+        let mut sized_for = self.sized_for.lock();
+        let limit = self.guard.current_limit();
+        if *sized_for == limit {
+            return;
+        }
+        for (partition, share) in self.shares(limit) {
+            partition.quota.set_limit(share);
+        }
+        *sized_for = limit;
+    }
+
+    /// Cota de cada partição com o limite `limit`; a soma não passa dele.
+    fn shares(&self, limit: usize) -> Vec<(&Partition, usize)> {
+        let mut partitions: Vec<&Partition> = self
+            .partitions
+            .values()
+            .chain(iter::once(&self.default_partition))
+            .collect();
+        partitions.sort_by(|a, b| {
+            b.weight
+                .total_cmp(&a.weight)
+                .then_with(|| a.index.cmp(&b.index))
+        });
+
+        let mut shares: Vec<(&Partition, usize)> = partitions
+            .into_iter()
+            .map(|partition| {
+                let share = if partition.weight == 0.0 {
+                    0
+                } else {
+                    (limit as f64 * partition.weight / self.total_weight).floor() as usize
+                };
+                (partition, share)
+            })
+            .collect();
+
+        let assigned: usize = shares.iter().map(|(_, share)| share).sum();
+        let remainder = limit.saturating_sub(assigned);
+        for (_, share) in shares
+            .iter_mut()
+            .filter(|(partition, _)| partition.weight > 0.0)
+            .take(remainder)
+        {
+            *share += 1;
+        }
+        shares
+    }
+
+    /// Cota garantida da partição de `key` com o limite atual.
+    pub fn share<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let partition = self.partition(key);
+        self.shares(self.current_limit())
+            .into_iter()
+            .find(|(candidate, _)| std::ptr::eq(*candidate, partition))
+            .map_or(0, |(_, share)| share)
+    }
+
+    /// O guard global, para `subscribe()`, `stats()` e demais observações.
+    pub fn guard(&self) -> &FlowGuard<S> {
+        &self.guard
+    }
+
+    /// Limite global decidido pela estratégia.
+    pub fn current_limit(&self) -> usize {
+        self.guard.current_limit()
+    }
+
+    /// Total de execuções em andamento, somando todas as partições.
+    pub fn in_flight(&self) -> usize {
+        self.guard.in_flight()
+    }
+
+    /// Execuções em andamento na partição de `key`.
+    pub fn partition_in_flight<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.partition(key).in_flight.load(Ordering::Acquire)
+    }
+
+    /// Fecha o guard: novas execuções e todas as que esperam, em qualquer
+    /// fila, falham com `FlowError::Closed` (veja [`FlowGuard::close`]).
+    pub fn close(&self) {
+        self.guard.close();
+        for partition in self
+            .partitions
+            .values()
+            .chain(iter::once(&self.default_partition))
+        {
+            partition.quota.close();
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.guard.is_closed()
+    }
+
+    /// Resolve quando nenhuma partição tiver execução em andamento.
+    pub async fn drain(&self) {
+        self.guard.drain().await
+    }
+}
+
+/// Presença de uma execução na partição; a vaga da cota, se houver, volta ao
+/// primeiro da fila da partição no `Drop`.
+struct PartitionPermit<'a> {
+    partition: &'a Partition,
+    _quota: Option<DynamicPermit>,
+}
+
+impl<'a> PartitionPermit<'a> {
+    fn new(partition: &'a Partition, quota: Option<DynamicPermit>) -> Self {
+        partition.in_flight.fetch_add(1, Ordering::AcqRel);
+        Self {
+            partition,
+            _quota: quota,
+        }
+    }
+}
+
+impl Drop for PartitionPermit<'_> {
+    fn drop(&mut self) {
+        self.partition.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
@@ -0,0 +1,232 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Exportador no formato texto do Prometheus
+ */
+
+use crate::stats::{StatsSnapshot, LATENCY_BUCKETS};
+use crate::{FlowGuard, LimitStrategy};
+use parking_lot::RwLock;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Valores de um guard no momento da coleta.
+struct GuardSample {
+    limit: usize,
+    in_flight: usize,
+    queue_len: usize,
+    stats: StatsSnapshot,
+}
+
+/// Apaga o tipo da estratégia para guardar guards diferentes juntos.
+trait MetricsSource: Send + Sync {
+    fn sample(&self) -> GuardSample;
+}
+
+impl<S: LimitStrategy + 'static> MetricsSource for FlowGuard<S> {
+    fn sample(&self) -> GuardSample {
+        GuardSample {
+            limit: self.current_limit(),
+            in_flight: self.in_flight(),
+            queue_len: self.queue_len(),
+            stats: self.stats(),
+        }
+    }
+}
+
+/// Renderiza as métricas de vários `FlowGuard` no formato de exposição em
+/// texto do Prometheus.
+///
+/// ```
+/// use flow_guard::prometheus::PrometheusExporter;
+/// use flow_guard::{FlowGuard, VegasStrategy};
+///
+/// let exporter = PrometheusExporter::new();
+/// let db = FlowGuard::new(VegasStrategy::new(10));
+/// exporter.register("db", &db);
+///
+/// assert!(exporter.render().contains(r#"flowguard_limit{guard="db"} 10"#));
+/// ```
+#[derive(Default)]
+pub struct PrometheusExporter {
+    guards: RwLock<Vec<(String, Arc<dyn MetricsSource>)>>,
+}
+
+impl PrometheusExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra um guard; suas métricas saem com o rótulo `guard="<name>"`.
+    ///
+    /// O exportador guarda um clone do guard (que compartilha o estado com o
+    /// original), então o registro vale para toda a vida do exportador.
+    pub fn register<S: LimitStrategy + 'static>(
+        &self,
+        name: impl Into<String>,
+        guard: &FlowGuard<S>,
+    ) {
+        self.guards
+            .write()
+            .push((name.into(), Arc::new(guard.clone())));
+    }
+
+    /// Gera o texto de exposição com todos os guards registrados.
+    pub fn render(&self) -> String {
+        let samples: Vec<(String, GuardSample)> = self
+            .guards
+            .read()
+            .iter()
+            .map(|(name, source)| (escape_label(name), source.sample()))
+            .collect();
+
+        let mut out = String::new();
+
+        gauge(
+            &mut out,
+            "flowguard_limit",
+            "Current concurrency limit.",
+            &samples,
+            |s| s.limit as u64,
+        );
+        gauge(
+            &mut out,
+            "flowguard_in_flight",
+            "Executions currently holding a permit.",
+            &samples,
+            |s| s.in_flight as u64,
+        );
+        gauge(
+            &mut out,
+            "flowguard_queue_depth",
+            "Executions waiting for a permit.",
+            &samples,
+            |s| s.queue_len as u64,
+        );
+        counter(
+            &mut out,
+            "flowguard_acquired_total",
+            "Permits granted.",
+            &samples,
+            |s| s.stats.acquired,
+        );
+        counter(
+            &mut out,
+            "flowguard_dropped_total",
+            "Executions shed without waiting.",
+            &samples,
+            |s| s.stats.dropped,
+        );
+        counter(
+            &mut out,
+            "flowguard_rejected_total",
+            "Executions rejected because the queue was full or the guard was closed.",
+            &samples,
+            |s| s.stats.rejected,
+        );
+        counter(
+            &mut out,
+            "flowguard_timed_out_total",
+            "Executions that gave up after the maximum queue wait.",
+            &samples,
+            |s| s.stats.timed_out,
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP flowguard_latency_seconds Latency of completed executions."
+        );
+        let _ = writeln!(out, "# TYPE flowguard_latency_seconds histogram");
+        for (name, sample) in &samples {
+            let latency = &sample.stats.latency;
+            for (le, count) in LATENCY_BUCKETS.iter().zip(&latency.buckets) {
+                let _ = writeln!(
+                    out,
+                    "flowguard_latency_seconds_bucket{{guard=\"{name}\",le=\"{le}\"}} {count}"
+                );
+            }
+            // `count` vem da mesma leitura dos buckets, então `+Inf` nunca fica
+            // abaixo do último bucket finito
+            let _ = writeln!(
+                out,
+                "flowguard_latency_seconds_bucket{{guard=\"{name}\",le=\"+Inf\"}} {}",
+                latency.count
+            );
+            let _ = writeln!(
+                out,
+                "flowguard_latency_seconds_sum{{guard=\"{name}\"}} {}",
+                latency.sum.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "flowguard_latency_seconds_count{{guard=\"{name}\"}} {}",
+                latency.count
+            );
+        }
+
+        out
+    }
+
+    /// Router com `GET /metrics`, para montar na aplicação com `merge`.
+    #[cfg(feature = "axum")]
+    pub fn router(self: Arc<Self>) -> axum::Router {
+        axum::Router::new()
+            .route("/metrics", axum::routing::get(metrics_handler))
+            .with_state(self)
+    }
+}
+
+/// Handler axum que responde com as métricas do exportador no estado.
+#[cfg(feature = "axum")]
+pub async fn metrics_handler(
+    axum::extract::State(exporter): axum::extract::State<Arc<PrometheusExporter>>,
+) -> impl axum::response::IntoResponse {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        exporter.render(),
+    )
+}
+
+fn gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    samples: &[(String, GuardSample)],
+    value: impl Fn(&GuardSample) -> u64,
+) {
+    family(out, name, help, "gauge", samples, value);
+}
+
+fn counter(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    samples: &[(String, GuardSample)],
+    value: impl Fn(&GuardSample) -> u64,
+) {
+    family(out, name, help, "counter", samples, value);
+}
+
+fn family(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: &str,
+    samples: &[(String, GuardSample)],
+    value: impl Fn(&GuardSample) -> u64,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (guard, sample) in samples {
+        let _ = writeln!(out, "{name}{{guard=\"{guard}\"}} {}", value(sample));
+    }
+}
+
+/// Escapa `\`, `"` e quebras de linha, como exige o formato de texto.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
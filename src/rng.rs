@@ -0,0 +1,43 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Gerador pseudoaleatório interno
+ */
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// xorshift64: determinístico para a mesma semente, rápido e sem dependências.
+/// Não serve para nada criptográfico.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Embaralha a semente (finalizador do splitmix64) para que sementes
+        // próximas gerem sequências distintas desde o primeiro valor
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+        // Zero é ponto fixo do xorshift
+        Self(state.max(1))
+    }
+
+    /// Semente sorteada só com std: o `RandomState` já é sorteado pelo SO.
+    pub(crate) fn from_entropy() -> Self {
+        Self::new(RandomState::new().build_hasher().finish())
+    }
+
+    /// Uniforme em `[0, 1)`.
+    pub(crate) fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Exponencial com média 1.
+    #[cfg(feature = "sim")]
+    pub(crate) fn exponential(&mut self) -> f64 {
+        -(1.0 - self.uniform()).ln()
+    }
+}
@@ -2,127 +2,419 @@
  * Semaphore dinâmico para FlowGuard - VERSÃO FINAL CORRIGIDA
  */
 
-use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::criticality::Criticality;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::Notify;
+use std::time::Duration;
+use tokio::sync::{oneshot, Notify};
+use tokio::time::Instant;
 
+/// Motivo de uma aquisição não ter conseguido permissão.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcquireError {
+    /// Nenhuma permissão livre no momento (apenas em `try_acquire`).
+    NoPermits,
+    /// A fila de espera já estava no tamanho máximo.
+    QueueFull,
+    /// O tempo máximo de espera na fila se esgotou.
+    Timeout,
+    /// O semáforo foi fechado e não concede mais permissões.
+    Closed,
+}
+
+/// Ordem de atendimento da fila de espera dentro de um mesmo nível de
+/// criticidade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum QueueDiscipline {
+    /// Primeiro a chegar, primeiro a ser atendido.
+    #[default]
+    Fifo,
+    /// O mais recente é atendido primeiro: sob sobrecarga, os mais antigos
+    /// provavelmente já desistiram no cliente.
+    Lifo,
+    /// FIFO normalmente; LIFO enquanto o waiter mais antigo estiver esperando
+    /// há mais de `threshold`, ou seja, enquanto a fila não esvazia (o
+    /// "adaptive LIFO" descrito pelo Facebook).
+    AdaptiveLifo {
+        #[cfg_attr(feature = "serde", serde(with = "crate::config::duration"))]
+        threshold: Duration,
+    },
+}
+
+/// Semáforo com limite ajustável em tempo de execução.
+///
+/// Em vez de um contador de permissões disponíveis, guarda o limite e o número
+/// de permissões em uso (`in_flight`). Uma aquisição só passa se
+/// `in_flight < limit`, então reduzir o limite tem efeito imediato: as
+/// permissões excedentes viram "dívida" e novas aquisições ficam bloqueadas até
+/// que o trabalho em andamento caia abaixo do novo limite.
+///
+/// Quem não consegue permissão entra em uma fila explícita, ordenada por
+/// [`Criticality`] e, dentro do mesmo nível, pela [`QueueDiscipline`]. Cada
+/// liberação entrega a permissão diretamente ao primeiro da fila.
+///
+/// Deve sempre ser usado atrás de um `Arc`: cada `DynamicPermit` guarda uma
+/// referência para a mesma instância e devolve a permissão a ela no `Drop`.
 #[derive(Debug)]
 pub struct DynamicSemaphore {
     max_permits: AtomicUsize,
-    available_permits: AtomicUsize,
-    notify: Notify,
+    in_flight: AtomicUsize,
+    waiting: AtomicUsize,
+    closed: AtomicBool,
+    queue: Mutex<WaitQueue>,
+    drained: Notify,
+    closing: Notify,
+}
+
+/// Posição na fila: menor é atendido antes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct WaiterKey {
+    rank: usize,
+    seq: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    since: Instant,
+    tx: oneshot::Sender<DynamicPermit>,
+}
+
+#[derive(Debug, Default)]
+struct WaitQueue {
+    waiters: BTreeMap<WaiterKey, Waiter>,
+    next_seq: u64,
+    discipline: QueueDiscipline,
+}
+
+impl WaitQueue {
+    fn push(&mut self, priority: Criticality) -> (WaiterKey, oneshot::Receiver<DynamicPermit>) {
+        let key = WaiterKey {
+            rank: priority.rank(),
+            seq: self.next_seq,
+        };
+        self.next_seq += 1;
+
+        let (tx, rx) = oneshot::channel();
+        let since = Instant::now();
+        self.waiters.insert(key, Waiter { since, tx });
+        (key, rx)
+    }
+
+    /// Retira o próximo a ser atendido: o nível mais crítico primeiro e,
+    /// dentro dele, o primeiro ou o último conforme a disciplina.
+    fn pop(&mut self) -> Option<oneshot::Sender<DynamicPermit>> {
+        let (&oldest, waiter) = self.waiters.first_key_value()?;
+
+        let lifo = match self.discipline {
+            QueueDiscipline::Fifo => false,
+            QueueDiscipline::Lifo => true,
+            QueueDiscipline::AdaptiveLifo { threshold } => waiter.since.elapsed() >= threshold,
+        };
+
+        let key = if lifo {
+            let level = oldest..=WaiterKey {
+                rank: oldest.rank,
+                seq: u64::MAX,
+            };
+            *self.waiters.range(level).next_back()?.0
+        } else {
+            oldest
+        };
+
+        self.waiters.remove(&key).map(|waiter| waiter.tx)
+    }
 }
 
 impl DynamicSemaphore {
     pub fn new(initial_permits: usize) -> Self {
         Self {
             max_permits: AtomicUsize::new(initial_permits),
-            available_permits: AtomicUsize::new(initial_permits),
-            notify: Notify::new(),
+            in_flight: AtomicUsize::new(0),
+            waiting: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            queue: Mutex::default(),
+            drained: Notify::new(),
+            closing: Notify::new(),
         }
     }
 
-    pub fn set_limit(&self, new_limit: usize) {
+    pub fn set_limit(self: &Arc<Self>, new_limit: usize) {
         let old_limit = self.max_permits.swap(new_limit, Ordering::SeqCst);
 
         if new_limit > old_limit {
-            // Adiciona novas permissões
-            let diff = new_limit - old_limit;
-            let old_available = self.available_permits.fetch_add(diff, Ordering::SeqCst);
-
-            // Se havia waiters bloqueados, notifica alguns
-            if old_available == 0 && diff > 0 {
-                // Notifica no máximo diff waiters
-                for _ in 0..diff.min(128) {
-                    // Limite razoável
-                    self.notify.notify_one();
-                }
-            }
+            // Entrega as novas permissões para quem está na fila
+            self.grant_waiters();
         }
-        // Para diminuir: permissões extras serão consumidas naturalmente
+        // Para diminuir: nada a fazer aqui. `try_reserve` compara `in_flight`
+        // com o novo limite, então a dívida bloqueia novas aquisições.
     }
 
-    pub async fn acquire(&self) -> Result<DynamicPermit, ()> {
-        loop {
-            // Tenta adquirir
-            if let Some(permit) = self.try_acquire() {
-                return Ok(permit);
+    /// Troca a ordem de atendimento dentro de cada nível de criticidade.
+    pub fn set_discipline(&self, discipline: QueueDiscipline) {
+        self.queue.lock().discipline = discipline;
+    }
+
+    /// Espera por uma permissão.
+    ///
+    /// `priority` define a posição na fila, `max_queue` limita quantas tarefas
+    /// podem estar esperando ao mesmo tempo e `timeout` limita quanto tempo
+    /// cada uma espera. `None` desliga o limite.
+    pub async fn acquire(
+        self: &Arc<Self>,
+        priority: Criticality,
+        max_queue: Option<usize>,
+        timeout: Option<Duration>,
+    ) -> Result<DynamicPermit, AcquireError> {
+        // Caminho rápido: permissão livre e ninguém na frente
+        match self.try_acquire() {
+            Err(AcquireError::NoPermits) => {}
+            result => return result,
+        }
+
+        let _slot = self.enter_queue(max_queue)?;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        let (key, mut rx) = {
+            let mut queue = self.queue.lock();
+
+            // Checa de novo com o lock: uma liberação concorrente pode ter
+            // aberto vaga antes de entrarmos na fila
+            if self.is_closed() {
+                return Err(AcquireError::Closed);
+            }
+            if self.try_reserve() {
+                return Ok(DynamicPermit::new(self));
             }
+            queue.push(priority)
+        };
+        // Sai da fila se a future for cancelada antes de receber a permissão
+        let _registration = Registration {
+            semaphore: self,
+            key,
+        };
 
-            // Espera por notificação
-            self.notify.notified().await;
+        let received = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, &mut rx).await {
+                Ok(received) => received,
+                Err(_) => {
+                    if self.queue.lock().waiters.remove(&key).is_some() {
+                        return Err(AcquireError::Timeout);
+                    }
+                    // Já fomos escolhidos: a permissão está a caminho
+                    rx.await
+                }
+            },
+            None => rx.await,
+        };
+
+        // Canal fechado sem permissão: o semáforo foi fechado
+        received.map_err(|_| AcquireError::Closed)
+    }
 
-            // IMPORTANTE: Após acordar, deve tentar novamente
-            // Outra task pode ter pegado a permissão antes
+    /// Reserva um lugar na fila de espera, respeitando o tamanho máximo.
+    fn enter_queue(&self, max_queue: Option<usize>) -> Result<QueueSlot<'_>, AcquireError> {
+        let position = self.waiting.fetch_add(1, Ordering::SeqCst);
+        // O slot é criado antes da checagem para que o Drop desfaça o incremento
+        let slot = QueueSlot { semaphore: self };
+
+        match max_queue {
+            Some(max) if position >= max => Err(AcquireError::QueueFull),
+            _ => Ok(slot),
+        }
+    }
+
+    /// Pega uma permissão livre sem esperar.
+    ///
+    /// Com alguém na fila retorna `NoPermits` mesmo que uma vaga tenha acabado
+    /// de abrir: ela pertence ao primeiro da fila.
+    pub fn try_acquire(self: &Arc<Self>) -> Result<DynamicPermit, AcquireError> {
+        if self.is_closed() {
+            return Err(AcquireError::Closed);
+        }
+        if self.waiting.load(Ordering::SeqCst) > 0 || !self.try_reserve() {
+            return Err(AcquireError::NoPermits);
         }
+        Ok(DynamicPermit::new(self))
     }
 
-    #[allow(dead_code)]
-    pub fn try_acquire(&self) -> Option<DynamicPermit> {
-        let mut current = self.available_permits.load(Ordering::SeqCst);
+    /// Pega uma permissão mesmo sem vaga; a excedente vira dívida, como após
+    /// uma redução do limite. Usado pelas cotas garantidas de
+    /// [`PartitionedFlowGuard`](crate::partitioned::PartitionedFlowGuard).
+    pub(crate) fn acquire_over_limit(self: &Arc<Self>) -> Result<DynamicPermit, AcquireError> {
+        if self.is_closed() {
+            return Err(AcquireError::Closed);
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        Ok(DynamicPermit::new(self))
+    }
+
+    /// Incrementa `in_flight` se ainda estiver abaixo do limite.
+    fn try_reserve(&self) -> bool {
+        let mut current = self.in_flight.load(Ordering::SeqCst);
 
         loop {
-            if current == 0 {
-                return None;
+            if current >= self.max_permits.load(Ordering::SeqCst) {
+                return false;
             }
 
-            match self.available_permits.compare_exchange_weak(
+            match self.in_flight.compare_exchange_weak(
                 current,
-                current - 1,
+                current + 1,
                 Ordering::SeqCst,
                 Ordering::Relaxed,
             ) {
-                Ok(_) => {
-                    return Some(DynamicPermit {
-                        semaphore: Arc::new(self.clone()),
-                    })
-                }
+                Ok(_) => return true,
                 Err(actual) => current = actual,
             }
         }
     }
 
+    /// Entrega permissões aos primeiros da fila enquanto houver vaga.
+    fn grant_waiters(self: &Arc<Self>) {
+        loop {
+            let waiter = {
+                let mut queue = self.queue.lock();
+                if queue.waiters.is_empty() || !self.try_reserve() {
+                    return;
+                }
+                queue.pop()
+            };
+
+            if let Some(waiter) = waiter {
+                // Se o waiter desistiu no meio do caminho, a permissão volta
+                // pelo `Drop` e segue para o próximo (fora do lock)
+                let _ = waiter.send(DynamicPermit::new(self));
+            }
+        }
+    }
+
+    /// Fecha o semáforo: novas aquisições falham com `Closed` e todas as
+    /// tarefas na fila de espera são acordadas para receberem o mesmo erro.
+    ///
+    /// Permissões já concedidas continuam válidas até serem liberadas.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // Descartar os senders acorda cada waiter com `Closed`
+        let waiters = std::mem::take(&mut self.queue.lock().waiters);
+        drop(waiters);
+        self.closing.notify_waiters();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Resolve quando o semáforo for fechado.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) async fn closed(&self) {
+        let closing = self.closing.notified();
+        tokio::pin!(closing);
+        closing.as_mut().enable();
+
+        if !self.is_closed() {
+            closing.await;
+        }
+    }
+
+    /// Resolve quando não houver nenhuma permissão em uso.
+    pub async fn drain(&self) {
+        loop {
+            let drained = self.drained.notified();
+            tokio::pin!(drained);
+            drained.as_mut().enable();
+
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+
+            drained.await;
+        }
+    }
+
     pub fn available_permits(&self) -> usize {
-        self.available_permits.load(Ordering::Relaxed)
+        self.current_limit().saturating_sub(self.in_flight())
     }
 
-    #[allow(dead_code)]
     pub fn current_limit(&self) -> usize {
         self.max_permits.load(Ordering::Relaxed)
     }
 
-    fn release(&self) {
-        let old = self.available_permits.fetch_add(1, Ordering::SeqCst);
+    /// Permissões atualmente em uso.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Tarefas esperando por uma permissão.
+    pub fn queue_len(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
 
-        // Se estava em 0, agora tem 1 permissão - notifica UM waiter
-        if old == 0 {
-            self.notify.notify_one();
+    fn release(self: &Arc<Self>) {
+        let previous = self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        if previous == 1 {
+            // Última permissão em uso: acorda quem está em `drain()`
+            self.drained.notify_waiters();
         }
 
-        // Garante que não exceda o limite máximo
-        let current = self.available_permits.load(Ordering::Relaxed);
-        let max = self.max_permits.load(Ordering::Relaxed);
-        if current > max {
-            // Se excedeu (devido a race condition), ajusta
-            self.available_permits.store(max, Ordering::Relaxed);
+        // `grant_waiters` só entrega se a dívida já foi paga, ou seja, se
+        // esta liberação realmente abriu uma vaga abaixo do limite.
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            self.grant_waiters();
         }
     }
 }
 
-impl Clone for DynamicSemaphore {
-    fn clone(&self) -> Self {
-        Self {
-            max_permits: AtomicUsize::new(self.max_permits.load(Ordering::Relaxed)),
-            available_permits: AtomicUsize::new(self.available_permits.load(Ordering::Relaxed)),
-            notify: Notify::new(),
-        }
+/// Registro na fila; removido no `Drop` se ninguém o atendeu, para que uma
+/// aquisição cancelada não receba permissões.
+struct Registration<'a> {
+    semaphore: &'a DynamicSemaphore,
+    key: WaiterKey,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.semaphore.queue.lock().waiters.remove(&self.key);
     }
 }
 
+/// Lugar ocupado na fila de espera; liberado no `Drop`, inclusive quando a
+/// future de aquisição é cancelada.
+struct QueueSlot<'a> {
+    semaphore: &'a DynamicSemaphore,
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.semaphore.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Permissão "owned": mantém o semáforo compartilhado vivo e devolve a
+/// permissão a ele quando é liberada (inclusive em pânico ou cancelamento).
+#[derive(Debug)]
 pub struct DynamicPermit {
     semaphore: Arc<DynamicSemaphore>,
 }
 
+impl DynamicPermit {
+    /// Chamado só depois de `in_flight` ter sido incrementado. A permissão
+    /// aponta para ESTE semáforo (não para uma cópia), senão o release nunca
+    /// chegaria ao contador real.
+    fn new(semaphore: &Arc<DynamicSemaphore>) -> Self {
+        Self {
+            semaphore: Arc::clone(semaphore),
+        }
+    }
+}
+
 impl Drop for DynamicPermit {
     fn drop(&mut self) {
         self.semaphore.release();
@@ -0,0 +1,361 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Simulação determinística com tempo virtual
+ */
+
+//! Simulação determinística de um `FlowGuard` contra um backend modelado.
+//!
+//! Tudo roda no relógio pausado do tokio: os `sleep` avançam o tempo virtual
+//! instantaneamente e na mesma ordem em toda execução, então um cenário de
+//! minutos termina em milissegundos e sempre produz o mesmo relatório para a
+//! mesma semente. Isso permite comparar estratégias em CI sem flakiness.
+//!
+//! ```
+//! use flow_guard::sim::{Backend, Scenario, ServiceTime};
+//! use flow_guard::{AimdStrategy, FlowGuard, VegasStrategy};
+//! use std::time::Duration;
+//!
+//! let scenario = Scenario::new(
+//!     Backend::new(20, ServiceTime::Exponential(Duration::from_millis(20))).with_failure_rate(0.01),
+//! )
+//! .with_arrival_rate(1500.0)
+//! .with_duration(Duration::from_secs(5));
+//!
+//! let vegas = scenario.simulate(FlowGuard::new(VegasStrategy::new(10)));
+//! let aimd = scenario.simulate(FlowGuard::new(AimdStrategy::new(10)));
+//! println!("vegas: {:.0} req/s, p99 {:?}", vegas.goodput, vegas.latency.p99);
+//! println!("aimd:  {:.0} req/s, p99 {:?}", aimd.goodput, aimd.latency.p99);
+//! ```
+
+use crate::error::FlowError;
+use crate::rng::Rng;
+use crate::{FlowGuard, LimitStrategy};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::{sleep, sleep_until, Instant};
+
+/// Distribuição do tempo de serviço do backend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceTime {
+    /// Sempre o mesmo tempo.
+    Constant(Duration),
+    /// Uniforme entre os dois valores.
+    Uniform(Duration, Duration),
+    /// Exponencial com a média dada (cauda longa, como filas M/M/c).
+    Exponential(Duration),
+}
+
+impl ServiceTime {
+    fn sample(&self, rng: &mut Rng) -> Duration {
+        match *self {
+            ServiceTime::Constant(time) => time,
+            ServiceTime::Uniform(min, max) => {
+                min + (max.saturating_sub(min)).mul_f64(rng.uniform())
+            }
+            ServiceTime::Exponential(mean) => mean.mul_f64(rng.exponential()),
+        }
+    }
+}
+
+/// Servidor modelado: `capacity` requisições atendidas em paralelo e as
+/// demais esperando em uma fila FIFO interna.
+#[derive(Debug, Clone)]
+pub struct Backend {
+    capacity: usize,
+    service_time: ServiceTime,
+    failure_rate: f64,
+    max_queue: Option<usize>,
+}
+
+impl Backend {
+    /// # Panics
+    ///
+    /// Se `capacity` for 0: nenhuma requisição seria atendida.
+    pub fn new(capacity: usize, service_time: ServiceTime) -> Self {
+        assert!(capacity > 0, "backend capacity must be at least 1");
+        Self {
+            capacity,
+            service_time,
+            failure_rate: 0.0,
+            max_queue: None,
+        }
+    }
+
+    /// Fração das requisições atendidas que falham (padrão: 0).
+    pub fn with_failure_rate(mut self, rate: f64) -> Self {
+        self.failure_rate = rate;
+        self
+    }
+
+    /// Tamanho máximo da fila interna; acima dele o backend falha na hora,
+    /// como um servidor respondendo 503 (padrão: ilimitada).
+    pub fn with_max_queue(mut self, max_queue: usize) -> Self {
+        self.max_queue = Some(max_queue);
+        self
+    }
+
+    /// Coloca o backend para atender, com os sorteios a partir de `seed`.
+    ///
+    /// Funciona também com o relógio real, por exemplo atrás de um servidor
+    /// HTTP de teste.
+    pub fn start(&self, seed: u64) -> RunningBackend {
+        RunningBackend {
+            config: self.clone(),
+            workers: Semaphore::new(self.capacity),
+            queued: AtomicUsize::new(0),
+            rng: Mutex::new(Rng::new(seed)),
+        }
+    }
+}
+
+/// Por que uma requisição falhou no backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendError {
+    /// A fila interna estava cheia.
+    Overloaded,
+    /// Falha sorteada pela `failure_rate`.
+    Failed,
+}
+
+/// Backend em execução, criado por [`Backend::start`].
+pub struct RunningBackend {
+    config: Backend,
+    workers: Semaphore,
+    queued: AtomicUsize,
+    rng: Mutex<Rng>,
+}
+
+impl RunningBackend {
+    /// Atende uma requisição: espera um worker livre e o tempo de serviço.
+    pub async fn call(&self) -> Result<(), BackendError> {
+        let queued = self.queued.fetch_add(1, Ordering::SeqCst);
+        if self.workers.available_permits() == 0
+            && self.config.max_queue.is_some_and(|max| queued >= max)
+        {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(BackendError::Overloaded);
+        }
+
+        let _worker = self
+            .workers
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+
+        let (service_time, failed) = {
+            let mut rng = self.rng.lock();
+            let service_time = self.config.service_time.sample(&mut rng);
+            (service_time, rng.uniform() < self.config.failure_rate)
+        };
+        sleep(service_time).await;
+
+        if failed {
+            Err(BackendError::Failed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Carga aberta (chegadas de Poisson) contra um [`Backend`].
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    backend: Backend,
+    arrival_rate: f64,
+    duration: Duration,
+    sample_interval: Duration,
+    seed: u64,
+}
+
+impl Scenario {
+    /// Padrões: 100 req/s durante 10s, limite amostrado a cada 100ms.
+    pub fn new(backend: Backend) -> Self {
+        Self {
+            backend,
+            arrival_rate: 100.0,
+            duration: Duration::from_secs(10),
+            sample_interval: Duration::from_millis(100),
+            seed: 1,
+        }
+    }
+
+    /// Requisições por segundo (média das chegadas de Poisson).
+    ///
+    /// # Panics
+    ///
+    /// Se `rate` não for um número finito maior que zero.
+    pub fn with_arrival_rate(mut self, rate: f64) -> Self {
+        assert!(
+            rate.is_finite() && rate > 0.0,
+            "arrival rate must be finite and greater than zero, got {rate}"
+        );
+        self.arrival_rate = rate;
+        self
+    }
+
+    /// Por quanto tempo (virtual) novas requisições chegam.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Intervalo entre as amostras de `SimReport::limit_trace`.
+    pub fn with_sample_interval(mut self, interval: Duration) -> Self {
+        self.sample_interval = interval;
+        self
+    }
+
+    /// Semente dos sorteios (chegadas, tempo de serviço e falhas).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Roda o cenário em um runtime próprio, de uma thread e com o relógio
+    /// pausado. Não pode ser chamado de dentro de outro runtime tokio.
+    pub fn simulate<S: LimitStrategy + 'static>(&self, guard: FlowGuard<S>) -> SimReport {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .expect("failed to build simulation runtime")
+            .block_on(self.run(guard))
+    }
+
+    /// Roda o cenário no runtime atual, que deve ser de uma thread e estar
+    /// com o relógio pausado (`#[tokio::test(start_paused = true)]`).
+    pub async fn run<S: LimitStrategy + 'static>(&self, guard: FlowGuard<S>) -> SimReport {
+        let guard = Arc::new(guard);
+        let backend = Arc::new(self.backend.start(self.seed ^ 0x9E37_79B9_7F4A_7C15));
+        let counters = Arc::new(Counters::default());
+        let latencies = Arc::new(Mutex::new(Vec::new()));
+        let start = Instant::now();
+        let end = start + self.duration;
+
+        let sampler = {
+            let guard = Arc::clone(&guard);
+            let interval = self.sample_interval;
+            tokio::spawn(async move {
+                let mut trace = Vec::new();
+                let mut next = start;
+                while next <= end {
+                    sleep_until(next).await;
+                    trace.push((next - start, guard.current_limit()));
+                    next += interval;
+                }
+                trace
+            })
+        };
+
+        let mut rng = Rng::new(self.seed);
+        let mut requests = JoinSet::new();
+        let mut arrival = start;
+        loop {
+            arrival += Duration::from_secs_f64(rng.exponential() / self.arrival_rate);
+            if arrival >= end {
+                break;
+            }
+            sleep_until(arrival).await;
+            counters.offered.fetch_add(1, Ordering::Relaxed);
+
+            let guard = Arc::clone(&guard);
+            let backend = Arc::clone(&backend);
+            let counters = Arc::clone(&counters);
+            let latencies = Arc::clone(&latencies);
+            requests.spawn(async move {
+                let sent = Instant::now();
+                match guard.run(backend.call()).await {
+                    Ok(()) => {
+                        counters.succeeded.fetch_add(1, Ordering::Relaxed);
+                        latencies.lock().push(sent.elapsed());
+                    }
+                    Err(FlowError::AppError(_)) => {
+                        counters.failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(_) => {
+                        counters.rejected.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+
+        while requests.join_next().await.is_some() {}
+        let elapsed = start.elapsed();
+        let limit_trace = sampler.await.expect("sampler task panicked");
+
+        let latencies = std::mem::take(&mut *latencies.lock());
+        let succeeded = counters.succeeded.load(Ordering::Relaxed);
+
+        SimReport {
+            offered: counters.offered.load(Ordering::Relaxed),
+            succeeded,
+            failed: counters.failed.load(Ordering::Relaxed),
+            rejected: counters.rejected.load(Ordering::Relaxed),
+            goodput: succeeded as f64 / elapsed.as_secs_f64(),
+            latency: LatencySummary::from_latencies(latencies),
+            limit_trace,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    offered: AtomicU64,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Resultado de uma simulação.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimReport {
+    /// Requisições geradas.
+    pub offered: u64,
+    /// Requisições concluídas com sucesso.
+    pub succeeded: u64,
+    /// Requisições que chegaram ao backend e falharam.
+    pub failed: u64,
+    /// Requisições recusadas pelo guard (fila cheia, timeout, ...).
+    pub rejected: u64,
+    /// Sucessos por segundo, do início até a última requisição terminar.
+    pub goodput: f64,
+    /// Latência ponta a ponta (fila do guard + backend) dos sucessos.
+    pub latency: LatencySummary,
+    /// Limite do guard ao longo do tempo: `(instante, limite)`.
+    pub limit_trace: Vec<(Duration, usize)>,
+}
+
+/// Percentis de latência.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencySummary {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    /// Percentis das latências dadas, em qualquer ordem.
+    pub fn from_latencies(mut latencies: Vec<Duration>) -> Self {
+        latencies.sort_unstable();
+        let sorted = &latencies;
+        let percentile = |q: f64| {
+            if sorted.is_empty() {
+                return Duration::ZERO;
+            }
+            let rank = (q * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        Self {
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            max: sorted.last().copied().unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Contadores e histograma de latência
+ */
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Limites superiores (em segundos) dos buckets do histograma de latência.
+#[cfg(feature = "metrics-prometheus")]
+pub const LATENCY_BUCKETS: [f64; 13] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Contadores atômicos mantidos por cada `FlowGuard`. Só existem com a
+/// feature `metrics-prometheus`, para não custar nada a quem não exporta.
+#[cfg(feature = "metrics-prometheus")]
+#[derive(Debug, Default)]
+pub(crate) struct FlowStats {
+    acquired: AtomicU64,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+    timed_out: AtomicU64,
+    latency: LatencyHistogram,
+}
+
+#[cfg(feature = "metrics-prometheus")]
+impl FlowStats {
+    pub(crate) fn acquired(&self) {
+        self.acquired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn timed_out(&self) {
+        self.timed_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn latency(&self, latency: Duration) {
+        self.latency.record(latency);
+    }
+
+    pub(crate) fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            acquired: self.acquired.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            timed_out: self.timed_out.load(Ordering::Relaxed),
+            latency: self.latency.snapshot(),
+        }
+    }
+}
+
+#[cfg(feature = "metrics-prometheus")]
+#[derive(Debug, Default)]
+struct LatencyHistogram {
+    /// Contagem por bucket (não cumulativa); o último é o `+Inf`.
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    sum_nanos: AtomicU64,
+}
+
+#[cfg(feature = "metrics-prometheus")]
+impl LatencyHistogram {
+    fn record(&self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|&le| secs <= le)
+            .unwrap_or(LATENCY_BUCKETS.len());
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_nanos
+            .fetch_add(latency.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|bucket| {
+                cumulative += bucket.load(Ordering::Relaxed);
+                cumulative
+            })
+            .collect();
+
+        // O total sai da mesma passada dos buckets: um contador separado,
+        // lido em outro momento, poderia ficar abaixo do último bucket e o
+        // histograma deixaria de ser monotônico
+        HistogramSnapshot {
+            buckets,
+            sum: Duration::from_nanos(self.sum_nanos.load(Ordering::Relaxed)),
+            count: cumulative,
+        }
+    }
+}
+
+/// Fotografia dos contadores de um `FlowGuard` (`FlowGuard::stats()`).
+#[cfg(feature = "metrics-prometheus")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    /// Permissões concedidas.
+    pub acquired: u64,
+    /// Execuções descartadas sem esperar (`FlowError::Dropped`).
+    pub dropped: u64,
+    /// Execuções recusadas por fila cheia ou guard fechado.
+    pub rejected: u64,
+    /// Execuções que desistiram após o tempo máximo na fila.
+    pub timed_out: u64,
+    /// Latência das execuções concluídas.
+    pub latency: HistogramSnapshot,
+}
+
+/// Histograma cumulativo de latência, nos buckets de [`LATENCY_BUCKETS`].
+#[cfg(feature = "metrics-prometheus")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistogramSnapshot {
+    /// Contagem cumulativa por bucket; o último elemento é o `+Inf`.
+    pub buckets: Vec<u64>,
+    pub sum: Duration,
+    /// Igual ao último elemento de `buckets`.
+    pub count: u64,
+}
+
+/// Quantas amostras de RTT um [`LimitSnapshot`] guarda.
+pub const RECENT_RTTS: usize = 16;
+
+/// Estado publicado por `FlowGuard::subscribe()` a cada mudança de limite.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LimitSnapshot {
+    /// Limite aplicado ao semáforo.
+    pub limit: usize,
+    /// Execuções segurando permissão no momento da mudança.
+    pub in_flight: usize,
+    /// Execuções esperando na fila no momento da mudança.
+    pub queue_len: usize,
+    /// Últimas latências repassadas à estratégia (até [`RECENT_RTTS`]), da
+    /// mais antiga para a mais recente.
+    pub recent_rtts: Vec<Duration>,
+}
+
+/// Últimas [`RECENT_RTTS`] latências, gravadas sem lock a cada execução e
+/// copiadas para o [`LimitSnapshot`] só quando ele é publicado.
+///
+/// Escritas concorrentes com a cópia podem trazer uma amostra mais nova no
+/// lugar de outra; para observabilidade isso basta.
+#[derive(Debug, Default)]
+pub(crate) struct RttRing {
+    /// Latências em nanossegundos.
+    slots: [AtomicU64; RECENT_RTTS],
+    /// Total de amostras já gravadas; a próxima vai em `written % RECENT_RTTS`.
+    written: AtomicUsize,
+}
+
+impl RttRing {
+    pub(crate) fn push(&self, rtt: Duration) {
+        let index = self.written.fetch_add(1, Ordering::Relaxed) % RECENT_RTTS;
+        self.slots[index].store(rtt.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Copia as amostras para `rtts`, da mais antiga para a mais recente.
+    pub(crate) fn copy_to(&self, rtts: &mut Vec<Duration>) {
+        let written = self.written.load(Ordering::Relaxed);
+        let len = written.min(RECENT_RTTS);
+        rtts.clear();
+        rtts.extend(
+            (written - len..written)
+                .map(|n| Duration::from_nanos(self.slots[n % RECENT_RTTS].load(Ordering::Relaxed))),
+        );
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Created by: Cleiton Augusto Correa Bezerra
+ * Project: FlowGuard - Adaptive Backpressure for Rust
+ * Algorithm: AIMD (Additive Increase / Multiplicative Decrease)
+ */
+
+#[cfg(feature = "serde")]
+use crate::config::StrategyConfig;
+#[cfg(feature = "serde")]
+use crate::error::ConfigError;
+use crate::LimitStrategy;
+#[cfg(feature = "serde")]
+use crate::ReloadableStrategy;
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Estratégia AIMD, a mesma ideia do controle de congestionamento do TCP Reno.
+///
+/// Cada sucesso soma `increase` ao limite; cada erro, ou cada execução mais
+/// lenta que `timeout`, multiplica o limite por `backoff_ratio`. Reage melhor
+/// que o Vegas a dependências que falham por timeout em vez de ficarem
+/// gradualmente mais lentas.
+pub struct AimdStrategy {
+    current_limit: AtomicUsize,
+    /// Trocados por inteiro na recarga a quente (`update_config`).
+    params: RwLock<AimdParams>,
+}
+
+struct AimdParams {
+    min_limit: usize,
+    max_limit: usize,
+    increase: usize,
+    backoff_ratio: f64,
+    timeout: Duration,
+}
+
+impl AimdStrategy {
+    /// # Panics
+    ///
+    /// Se `initial_limit` for zero.
+    pub fn new(initial_limit: usize) -> Self {
+        assert!(initial_limit >= 1, "initial_limit must be at least 1");
+        Self {
+            current_limit: AtomicUsize::new(initial_limit),
+            params: RwLock::new(AimdParams {
+                min_limit: 1,
+                max_limit: initial_limit.saturating_mul(10),
+                increase: 1,
+                backoff_ratio: 0.9,
+                timeout: Duration::from_secs(5),
+            }),
+        }
+    }
+
+    /// O limite atual sobe até `min_limit`, se estiver abaixo.
+    ///
+    /// # Panics
+    ///
+    /// Se `min_limit` for zero: com limite 0 o guard não admite nada e o
+    /// corte multiplicativo nunca sairia de 0.
+    pub fn with_min_limit(mut self, min_limit: usize) -> Self {
+        assert!(min_limit >= 1, "min_limit must be at least 1");
+        self.params.get_mut().min_limit = min_limit;
+        self.clamp_limit();
+        self
+    }
+
+    /// O limite atual desce até `max_limit`, se estiver acima.
+    pub fn with_max_limit(mut self, max_limit: usize) -> Self {
+        self.params.get_mut().max_limit = max_limit;
+        self.clamp_limit();
+        self
+    }
+
+    /// Quanto o limite cresce a cada sucesso (padrão: 1).
+    pub fn with_increase(mut self, increase: usize) -> Self {
+        self.params.get_mut().increase = increase;
+        self
+    }
+
+    /// Fator aplicado ao limite em cada erro (padrão: 0.9).
+    ///
+    /// # Panics
+    ///
+    /// Se `ratio` não estiver no intervalo `(0.0, 1.0)`.
+    pub fn with_backoff_ratio(mut self, ratio: f64) -> Self {
+        assert!(
+            ratio > 0.0 && ratio < 1.0,
+            "backoff_ratio must be between 0.0 and 1.0 (exclusive), got {ratio}"
+        );
+        self.params.get_mut().backoff_ratio = ratio;
+        self
+    }
+
+    /// Latência a partir da qual uma execução bem-sucedida conta como erro
+    /// (padrão: 5s).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.params.get_mut().timeout = timeout;
+        self
+    }
+
+    /// Mantém o limite em `[min_limit, max_limit]`; o mínimo prevalece se
+    /// os dois se cruzarem.
+    fn clamp_limit(&mut self) {
+        let params = self.params.get_mut();
+        let limit = self.current_limit.get_mut();
+        *limit = (*limit).min(params.max_limit).max(params.min_limit);
+    }
+
+    fn decrease(&self, params: &AimdParams) {
+        let _ = self
+            .current_limit
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |limit| {
+                let reduced = (limit as f64 * params.backoff_ratio) as usize;
+                Some(reduced.max(params.min_limit))
+            });
+    }
+}
+
+impl LimitStrategy for AimdStrategy {
+    fn current_limit(&self) -> usize {
+        self.current_limit.load(Ordering::Relaxed)
+    }
+
+    fn on_success(&self, latency: Duration) {
+        let params = self.params.read();
+        if latency > params.timeout {
+            self.decrease(&params);
+            record_reason("timeout");
+            return;
+        }
+
+        let increased =
+            self.current_limit
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |limit| {
+                    if limit >= params.max_limit {
+                        return None;
+                    }
+                    Some((limit + params.increase).min(params.max_limit))
+                });
+        if increased.is_ok() {
+            record_reason("additive_increase");
+        }
+    }
+
+    fn on_error(&self) {
+        self.decrease(&self.params.read());
+        record_reason("error");
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ReloadableStrategy for AimdStrategy {
+    /// Troca limites, `increase`, `backoff_ratio` e `timeout`.
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        let StrategyConfig::Aimd(config) = config else {
+            return Err(ConfigError::StrategyMismatch {
+                expected: "aimd",
+                found: config.kind(),
+            });
+        };
+
+        let params = config.build()?.params.into_inner();
+        let (min_limit, max_limit) = (params.min_limit, params.max_limit);
+        *self.params.write() = params;
+
+        let _ = self
+            .current_limit
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |limit| {
+                Some(limit.clamp(min_limit, max_limit))
+            });
+        Ok(())
+    }
+}
+
+/// Explica a mudança de limite no span `limit_update` do `FlowGuard` (veja
+/// [`LimitStrategy`]).
+fn record_reason(reason: &'static str) {
+    tracing::Span::current().record("reason", reason);
+}
@@ -0,0 +1,59 @@
+/*
+ * Created by: Cleiton Augusto Correa Bezerra
+ * Project: FlowGuard - Adaptive Backpressure for Rust
+ * Limite fixo
+ */
+
+#[cfg(feature = "serde")]
+use crate::config::StrategyConfig;
+#[cfg(feature = "serde")]
+use crate::error::ConfigError;
+use crate::LimitStrategy;
+#[cfg(feature = "serde")]
+use crate::ReloadableStrategy;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Limite constante, que ignora latências e erros.
+///
+/// Útil como linha de base em comparações e para desligar a adaptação via
+/// configuração sem trocar o tipo do guard. O limite só muda por
+/// `update_config`.
+#[derive(Debug)]
+pub struct FixedStrategy {
+    limit: AtomicUsize,
+}
+
+impl FixedStrategy {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: AtomicUsize::new(limit),
+        }
+    }
+}
+
+impl LimitStrategy for FixedStrategy {
+    fn current_limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    fn on_success(&self, _latency: Duration) {}
+
+    fn on_error(&self) {}
+}
+
+#[cfg(feature = "serde")]
+impl ReloadableStrategy for FixedStrategy {
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        let StrategyConfig::Fixed(config) = config else {
+            return Err(ConfigError::StrategyMismatch {
+                expected: "fixed",
+                found: config.kind(),
+            });
+        };
+
+        let limit = config.build()?.current_limit();
+        self.limit.store(limit, Ordering::Relaxed);
+        Ok(())
+    }
+}
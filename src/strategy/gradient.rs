@@ -0,0 +1,283 @@
+/*
+ * Created by: Cleiton Augusto Correa Bezerra
+ * Project: FlowGuard - Adaptive Backpressure for Rust
+ * Algorithm: Gradient2 (baseado no concurrency-limits da Netflix)
+ */
+
+#[cfg(feature = "serde")]
+use crate::config::StrategyConfig;
+#[cfg(feature = "serde")]
+use crate::error::ConfigError;
+use crate::LimitStrategy;
+#[cfg(feature = "serde")]
+use crate::ReloadableStrategy;
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Amostras usadas como média simples antes de a EMA de longo prazo assumir.
+const WARMUP_SAMPLES: usize = 10;
+
+/// Estratégia baseada no gradiente entre RTT de longo e de curto prazo.
+///
+/// A cada amostra:
+///
+/// 1. o RTT de longo prazo é atualizado por uma média móvel exponencial;
+/// 2. `gradient = clamp(tolerance * long_rtt / short_rtt, 0.5, 1.0)`;
+/// 3. `novo = limite * gradient + queue_size`, suavizado por `smoothing`.
+///
+/// Enquanto a latência atual não passa de `tolerance` vezes a de longo prazo
+/// o gradiente fica em 1.0 e o limite cresce pela folga `queue_size`. Por
+/// comparar razões em vez de diferenças absolutas (como `alpha`/`beta` do
+/// Vegas), tolera melhor latências com alta variância.
+///
+/// Com menos da metade do limite em uso (informado pelo `FlowGuard` via
+/// `on_success_with_in_flight`), a amostra só atualiza o RTT de longo prazo:
+/// a latência baixa não prova que um limite maior seria suportado, e sem essa
+/// checagem o limite subiria até `max_limit` com pouca carga e a primeira
+/// rajada passaria direto.
+pub struct GradientStrategy {
+    current_limit: AtomicUsize,
+    state: Mutex<GradientState>,
+    /// Trocados por inteiro na recarga a quente (`update_config`). Lido
+    /// antes de travar `state`.
+    params: RwLock<GradientParams>,
+}
+
+struct GradientParams {
+    min_limit: usize,
+    max_limit: usize,
+    smoothing: f64,
+    tolerance: f64,
+    queue_size: usize,
+    long_window: usize,
+}
+
+struct GradientState {
+    /// Limite estimado com casas decimais; `current_limit` guarda o arredondado.
+    estimated_limit: f64,
+    /// RTT de longo prazo em segundos.
+    long_rtt: f64,
+    samples: usize,
+}
+
+impl GradientStrategy {
+    /// # Panics
+    ///
+    /// Se `initial_limit` for zero.
+    pub fn new(initial_limit: usize) -> Self {
+        assert!(initial_limit >= 1, "initial_limit must be at least 1");
+        Self {
+            current_limit: AtomicUsize::new(initial_limit),
+            state: Mutex::new(GradientState {
+                estimated_limit: initial_limit as f64,
+                long_rtt: 0.0,
+                samples: 0,
+            }),
+            params: RwLock::new(GradientParams {
+                min_limit: 1,
+                max_limit: initial_limit.saturating_mul(10),
+                smoothing: 0.2,
+                tolerance: 1.5,
+                queue_size: 4,
+                long_window: 600,
+            }),
+        }
+    }
+
+    /// O limite atual sobe até `min_limit`, se estiver abaixo.
+    ///
+    /// # Panics
+    ///
+    /// Se `min_limit` for zero: com limite 0 o guard não admite nada e não
+    /// chega amostra que o faça subir.
+    pub fn with_min_limit(mut self, min_limit: usize) -> Self {
+        assert!(min_limit >= 1, "min_limit must be at least 1");
+        self.params.get_mut().min_limit = min_limit;
+        self.clamp_limit();
+        self
+    }
+
+    /// O limite atual desce até `max_limit`, se estiver acima.
+    pub fn with_max_limit(mut self, max_limit: usize) -> Self {
+        self.params.get_mut().max_limit = max_limit;
+        self.clamp_limit();
+        self
+    }
+
+    /// Peso da nova estimativa em cada atualização (padrão: 0.2).
+    ///
+    /// # Panics
+    ///
+    /// Se `smoothing` não estiver no intervalo `(0.0, 1.0]`.
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        assert!(
+            smoothing > 0.0 && smoothing <= 1.0,
+            "smoothing must be between 0.0 (exclusive) and 1.0, got {smoothing}"
+        );
+        self.params.get_mut().smoothing = smoothing;
+        self
+    }
+
+    /// Quantas vezes o RTT curto pode exceder o longo antes de o limite
+    /// começar a cair (padrão: 1.5).
+    ///
+    /// # Panics
+    ///
+    /// Se `tolerance` for menor que 1.0.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        assert!(
+            tolerance >= 1.0,
+            "tolerance must be at least 1.0, got {tolerance}"
+        );
+        self.params.get_mut().tolerance = tolerance;
+        self
+    }
+
+    /// Folga somada ao limite em cada atualização (padrão: 4).
+    pub fn with_queue_size(mut self, queue_size: usize) -> Self {
+        self.params.get_mut().queue_size = queue_size;
+        self
+    }
+
+    /// Tamanho, em amostras, da janela da média exponencial de longo prazo
+    /// (padrão: 600).
+    pub fn with_long_window(mut self, samples: usize) -> Self {
+        self.params.get_mut().long_window = samples.max(1);
+        self
+    }
+
+    /// Mantém o limite em `[min_limit, max_limit]`; o mínimo prevalece se
+    /// os dois se cruzarem.
+    fn clamp_limit(&mut self) {
+        let params = self.params.get_mut();
+        let limit = self.current_limit.get_mut();
+        *limit = (*limit).min(params.max_limit).max(params.min_limit);
+        self.state.get_mut().estimated_limit = *limit as f64;
+    }
+
+    /// RTT de longo prazo atual (zero antes da primeira amostra).
+    pub fn long_rtt(&self) -> Duration {
+        Duration::from_secs_f64(self.state.lock().long_rtt)
+    }
+
+    fn apply(
+        &self,
+        params: &GradientParams,
+        state: &mut GradientState,
+        gradient: f64,
+        queue_size: f64,
+    ) {
+        let limit = state.estimated_limit;
+        let target = limit * gradient + queue_size;
+        let smoothed = limit * (1.0 - params.smoothing) + target * params.smoothing;
+
+        state.estimated_limit = params.bounded(smoothed);
+        self.current_limit
+            .store(state.estimated_limit as usize, Ordering::Relaxed);
+    }
+}
+
+impl LimitStrategy for GradientStrategy {
+    fn current_limit(&self) -> usize {
+        self.current_limit.load(Ordering::Relaxed)
+    }
+
+    /// Sem saber quantas execuções estão em andamento, trata o limite como
+    /// totalmente em uso.
+    fn on_success(&self, latency: Duration) {
+        self.on_success_with_in_flight(latency, usize::MAX);
+    }
+
+    fn on_success_with_in_flight(&self, latency: Duration, in_flight: usize) {
+        let short_rtt = latency.as_secs_f64();
+        if short_rtt <= 0.0 {
+            return; // Evita divisão por zero
+        }
+
+        let params = self.params.read();
+        let mut state = self.state.lock();
+
+        // 1. Atualiza o RTT de longo prazo (média simples no aquecimento)
+        state.samples += 1;
+        if state.samples <= WARMUP_SAMPLES {
+            let n = state.samples as f64;
+            state.long_rtt += (short_rtt - state.long_rtt) / n;
+        } else {
+            let factor = 2.0 / (params.long_window as f64 + 1.0);
+            state.long_rtt += (short_rtt - state.long_rtt) * factor;
+        }
+
+        // Se o longo prazo ficou muito acima do curto (a carga caiu), deixa
+        // ele decair mais rápido para não mascarar a próxima subida
+        if state.long_rtt / short_rtt > 2.0 {
+            state.long_rtt *= 0.95;
+        }
+
+        // Limitado pela aplicação, não pelo limite: não há o que ajustar
+        if (in_flight as f64) < state.estimated_limit / 2.0 {
+            return;
+        }
+
+        // 2. Gradiente entre longo e curto prazo
+        let gradient = (params.tolerance * state.long_rtt / short_rtt).clamp(0.5, 1.0);
+
+        // 3. Novo limite suavizado
+        self.apply(&params, &mut state, gradient, params.queue_size as f64);
+        let reason = if gradient < 1.0 {
+            "latency_above_tolerance"
+        } else {
+            "queue_allowance"
+        };
+        record_reason(reason, gradient, state.long_rtt);
+    }
+
+    fn on_error(&self) {
+        // Erro = sobrecarga: aplica o gradiente mínimo, sem a folga da fila
+        let params = self.params.read();
+        let mut state = self.state.lock();
+        self.apply(&params, &mut state, 0.5, 0.0);
+        record_reason("error", 0.5, state.long_rtt);
+    }
+}
+
+impl GradientParams {
+    /// `limit` dentro de `[min_limit, max_limit]`, sem entrar em pânico se os
+    /// dois se cruzarem (o mínimo prevalece).
+    fn bounded(&self, limit: f64) -> f64 {
+        limit.min(self.max_limit as f64).max(self.min_limit as f64)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ReloadableStrategy for GradientStrategy {
+    /// Troca limites, `smoothing`, `tolerance`, `queue_size` e `long_window`.
+    /// O RTT de longo prazo acumulado é mantido.
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        let StrategyConfig::Gradient(config) = config else {
+            return Err(ConfigError::StrategyMismatch {
+                expected: "gradient",
+                found: config.kind(),
+            });
+        };
+
+        let built = config.build()?.params.into_inner();
+        let mut params = self.params.write();
+        *params = built;
+
+        let mut state = self.state.lock();
+        state.estimated_limit = params.bounded(state.estimated_limit);
+        self.current_limit
+            .store(state.estimated_limit as usize, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Explica a mudança de limite no span `limit_update` do `FlowGuard` (veja
+/// [`LimitStrategy`]).
+fn record_reason(reason: &'static str, gradient: f64, long_rtt: f64) {
+    let span = tracing::Span::current();
+    span.record("reason", reason);
+    span.record("gradient", gradient);
+    span.record("long_rtt_us", (long_rtt * 1e6) as u64);
+}
@@ -1,8 +1,16 @@
 /* * Created and Developed by: Cleiton Augusto Correa Bezerra
  */
 
+pub mod aimd;
+pub mod fixed;
+pub mod gradient;
 pub mod vegas; // Declara o sub-módulo vegas.rs
+pub mod windowed;
 
 // Re-exporta para que o usuário possa usar strategy::VegasStrategy
 // em vez de strategy::vegas::VegasStrategy
-pub use vegas::VegasStrategy;
+pub use aimd::AimdStrategy;
+pub use fixed::FixedStrategy;
+pub use gradient::GradientStrategy;
+pub use vegas::{VegasBuilder, VegasStrategy};
+pub use windowed::{Aggregation, WindowedStrategy};
@@ -4,41 +4,221 @@
  * Algorithm: Optimized TCP Vegas for Concurrency Control
  */
 
+#[cfg(feature = "serde")]
+use crate::config::StrategyConfig;
+use crate::error::ConfigError;
+use crate::rng::Rng;
 use crate::LimitStrategy;
-use parking_lot::RwLock;
+#[cfg(feature = "serde")]
+use crate::ReloadableStrategy;
+use parking_lot::{Mutex, RwLock};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
+use tokio::time::Instant;
+
+/// Função do limite atual usada para `alpha`, `beta` e `threshold`.
+pub type LimitFn = Box<dyn Fn(usize) -> f64 + Send + Sync>;
 
 pub struct VegasStrategy {
     current_limit: AtomicUsize,
-    base_rtt: RwLock<Duration>,
-    alpha: f64,
-    beta: f64,
+    rtt: Mutex<RttState>,
+    /// Trocados por inteiro na recarga a quente (`update_config`).
+    params: RwLock<VegasParams>,
+}
+
+/// Parâmetros ajustáveis. Para evitar deadlock, quem precisa dos dois locks
+/// pega `params` antes de `rtt`.
+struct VegasParams {
+    alpha: LimitFn,
+    beta: LimitFn,
+    threshold: Option<LimitFn>,
     min_limit: usize,
     max_limit: usize,
+    base_rtt_window: Option<usize>,
+    probe: Option<ProbeConfig>,
+}
+
+/// Fase de sondagem: a cada `interval` amostras (com jitter) o limite cai para
+/// `limit` até chegarem `samples` amostras de execuções admitidas depois da
+/// queda, e o menor RTT visto nelas vira o novo RTT base.
+#[derive(Debug, Clone, Copy)]
+struct ProbeConfig {
+    interval: usize,
+    jitter: f64,
+    limit: Option<usize>,
+    samples: usize,
+}
+
+struct RttState {
+    base_rtt: Duration,
+    /// Menor RTT da janela corrente (envelhecimento do RTT base).
+    window_min: Duration,
+    window_count: usize,
+    samples_since_probe: usize,
+    next_probe_at: usize,
+    probing: Option<Probe>,
+    last_probe: Option<Instant>,
+    rng: Rng,
+}
+
+struct Probe {
+    started: Instant,
+    saved_limit: usize,
+    min_rtt: Duration,
+    samples: usize,
+}
+
+impl RttState {
+    fn new(base_rtt: Duration, rng: Rng) -> Self {
+        Self {
+            base_rtt,
+            window_min: Duration::MAX,
+            window_count: 0,
+            samples_since_probe: 0,
+            next_probe_at: 0,
+            probing: None,
+            last_probe: None,
+            rng,
+        }
+    }
+
+    fn schedule_probe(&mut self, config: &ProbeConfig) {
+        let jitter = 1.0 + config.jitter * self.rng.uniform();
+        self.samples_since_probe = 0;
+        self.next_probe_at = (config.interval as f64 * jitter).round() as usize;
+    }
 }
 
 impl VegasStrategy {
     pub fn new(initial_limit: usize) -> Self {
         Self {
             current_limit: AtomicUsize::new(initial_limit),
-            base_rtt: RwLock::new(Duration::from_millis(1000)),
-            alpha: 2.0,
-            beta: 4.0,
-            min_limit: 1,
-            max_limit: initial_limit * 10,
+            rtt: Mutex::new(RttState::new(
+                Duration::from_millis(1000),
+                Rng::from_entropy(),
+            )),
+            params: RwLock::new(VegasParams {
+                alpha: constant(2.0),
+                beta: constant(4.0),
+                threshold: None,
+                min_limit: 1,
+                max_limit: initial_limit.saturating_mul(10),
+                base_rtt_window: None,
+                probe: None,
+            }),
         }
     }
 
+    /// Construtor completo, com validação dos parâmetros.
+    ///
+    /// ```
+    /// use flow_guard::VegasStrategy;
+    ///
+    /// // Limiares que crescem com o limite, como no Vegas de referência
+    /// let strategy = VegasStrategy::builder()
+    ///     .initial_limit(20)
+    ///     .max_limit(10_000)
+    ///     .alpha_fn(|limit| 3.0 * (limit as f64).log10())
+    ///     .beta_fn(|limit| 6.0 * (limit as f64).log10())
+    ///     .threshold_fn(|limit| (limit as f64).log10())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> VegasBuilder {
+        VegasBuilder::default()
+    }
+
     pub fn with_alpha(mut self, alpha: f64) -> Self {
-        self.alpha = alpha;
+        self.params.get_mut().alpha = constant(alpha);
         self
     }
 
     pub fn with_beta(mut self, beta: f64) -> Self {
-        self.beta = beta;
+        self.params.get_mut().beta = constant(beta);
         self
     }
+
+    /// RTT base (sem carga) usado na estimativa de fila.
+    pub fn base_rtt(&self) -> Duration {
+        self.rtt.lock().base_rtt
+    }
+
+    /// Quando a última sondagem terminou, se alguma já aconteceu (relógio do
+    /// tokio, que respeita o tempo pausado em testes e simulações).
+    pub fn last_probe(&self) -> Option<Instant> {
+        self.rtt.lock().last_probe
+    }
+
+    /// Se a estratégia está no meio de uma sondagem (limite reduzido).
+    pub fn is_probing(&self) -> bool {
+        self.rtt.lock().probing.is_some()
+    }
+
+    /// Atualiza o RTT base e as fases de sondagem. Retorna o RTT base a usar
+    /// na estimativa, ou `None` se o limite não deve ser ajustado agora.
+    fn observe_rtt(&self, params: &VegasParams, latency: Duration) -> Option<Duration> {
+        let mut rtt = self.rtt.lock();
+
+        // Sondagem em andamento: só mede, não mexe no limite. Execuções que
+        // começaram antes da queda do limite mediram a latência com a carga
+        // antiga e não contam para o RTT base sem carga. O início é medido no
+        // relógio do tokio, que o `trace::replay` avança junto com o trace
+        if let Some(probe) = rtt.probing.as_mut() {
+            if latency > probe.started.elapsed() {
+                return None;
+            }
+            probe.min_rtt = probe.min_rtt.min(latency);
+            probe.samples += 1;
+
+            let config = params.probe.as_ref()?;
+            if probe.samples >= config.samples {
+                let probe = rtt.probing.take()?;
+                rtt.base_rtt = probe.min_rtt;
+                rtt.window_min = Duration::MAX;
+                rtt.window_count = 0;
+                rtt.last_probe = Some(Instant::now());
+                rtt.schedule_probe(config);
+                self.current_limit
+                    .store(probe.saved_limit, Ordering::Relaxed);
+                record_reason("probe_end", rtt.base_rtt, None);
+            }
+            return None;
+        }
+
+        // Mínimo com envelhecimento: ao fechar a janela, o RTT base passa a
+        // ser o menor valor visto nela (e pode subir)
+        rtt.base_rtt = rtt.base_rtt.min(latency);
+        if let Some(window) = params.base_rtt_window {
+            rtt.window_min = rtt.window_min.min(latency);
+            rtt.window_count += 1;
+            if rtt.window_count >= window {
+                rtt.base_rtt = rtt.window_min;
+                rtt.window_min = Duration::MAX;
+                rtt.window_count = 0;
+            }
+        }
+
+        if let Some(config) = &params.probe {
+            rtt.samples_since_probe += 1;
+            if rtt.next_probe_at == 0 {
+                rtt.schedule_probe(config);
+            } else if rtt.samples_since_probe >= rtt.next_probe_at {
+                let saved_limit = self.current_limit.load(Ordering::Relaxed);
+                let probe_limit = config.limit.unwrap_or(params.min_limit).min(saved_limit);
+                self.current_limit.store(probe_limit, Ordering::Relaxed);
+                record_reason("probe_start", rtt.base_rtt, None);
+                rtt.probing = Some(Probe {
+                    started: Instant::now(),
+                    saved_limit,
+                    min_rtt: Duration::MAX,
+                    samples: 0,
+                });
+                return None;
+            }
+        }
+
+        Some(rtt.base_rtt)
+    }
 }
 
 impl LimitStrategy for VegasStrategy {
@@ -47,42 +227,370 @@ impl LimitStrategy for VegasStrategy {
     }
 
     fn on_success(&self, latency: Duration) {
-        {
-            let current_base = self.base_rtt.read();
-            if latency < *current_base {
-                drop(current_base);
-                let mut write_base = self.base_rtt.write();
-                if latency < *write_base {
-                    *write_base = latency;
-                }
-            }
+        if latency.as_nanos() == 0 {
+            return; // Evita divisão por zero
         }
 
-        let base_rtt = *self.base_rtt.read();
+        let params = self.params.read();
+        let Some(base_rtt) = self.observe_rtt(&params, latency) else {
+            return;
+        };
         let limit = self.current_limit.load(Ordering::Relaxed);
 
-        if base_rtt.as_nanos() == 0 || latency.as_nanos() == 0 {
-            return; // Evita divisão por zero
-        }
-
         let expected_throughput = limit as f64 / base_rtt.as_secs_f64();
         let actual_throughput = limit as f64 / latency.as_secs_f64();
         let diff = (expected_throughput - actual_throughput) * base_rtt.as_secs_f64();
 
-        if diff > self.beta {
-            if limit > self.min_limit {
-                self.current_limit.fetch_sub(1, Ordering::Relaxed);
+        let fast_growth = params
+            .threshold
+            .as_ref()
+            .is_some_and(|threshold| diff <= threshold(limit));
+
+        let (old_limit, new_limit, reason) = if fast_growth {
+            // Fila praticamente vazia: cresce `beta(limit)` de uma vez, como
+            // no Vegas de referência
+            let step = ((params.beta)(limit).ceil() as usize).max(1);
+            let new_limit = (limit + step).min(params.max_limit);
+            self.current_limit.store(new_limit, Ordering::Relaxed);
+            (limit, new_limit, "fast_growth")
+        } else if diff > (params.beta)(limit) {
+            if limit <= params.min_limit {
+                return;
             }
-        } else if diff < self.alpha && limit < self.max_limit {
-            self.current_limit.fetch_add(1, Ordering::Relaxed);
+            let old_limit = self.current_limit.fetch_sub(1, Ordering::Relaxed);
+            (old_limit, old_limit - 1, "queue_above_beta")
+        } else if diff < (params.alpha)(limit) && limit < params.max_limit {
+            let old_limit = self.current_limit.fetch_add(1, Ordering::Relaxed);
+            (old_limit, old_limit + 1, "queue_below_alpha")
+        } else {
+            return;
+        };
+
+        if old_limit != new_limit {
+            record_reason(reason, base_rtt, Some(diff));
         }
     }
 
     fn on_error(&self) {
+        let params = self.params.read();
+        if params.probe.is_some() {
+            // Durante a sondagem o corte vale para o limite que será restaurado
+            let mut rtt = self.rtt.lock();
+            if let Some(probe) = rtt.probing.as_mut() {
+                probe.saved_limit = params.decreased(probe.saved_limit);
+                return;
+            }
+        }
+
         let limit = self.current_limit.load(Ordering::Relaxed);
-        if limit > self.min_limit {
-            let new_limit = (limit * 3 / 4).max(self.min_limit);
+        if limit > params.min_limit {
+            let new_limit = params.decreased(limit);
             self.current_limit.store(new_limit, Ordering::Relaxed);
+            record_reason("error", self.base_rtt(), None);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ReloadableStrategy for VegasStrategy {
+    /// Troca `alpha`, `beta`, `threshold`, limites, janela do RTT base e
+    /// sondagem. `initial_limit` e `initial_base_rtt` só valem na construção.
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        let StrategyConfig::Vegas(config) = config else {
+            return Err(ConfigError::StrategyMismatch {
+                expected: "vegas",
+                found: config.kind(),
+            });
+        };
+
+        // Valida exatamente como na construção
+        let params = config.build()?.params.into_inner();
+        let (min_limit, max_limit) = (params.min_limit, params.max_limit);
+        let probe_enabled = params.probe.is_some();
+        *self.params.write() = params;
+
+        let mut rtt = self.rtt.lock();
+        // Próxima amostra reagenda a sondagem com o intervalo novo
+        rtt.next_probe_at = 0;
+        if let Some(probe) = rtt.probing.as_mut() {
+            probe.saved_limit = probe.saved_limit.clamp(min_limit, max_limit);
         }
+        // Sondagem desligada no meio: restaura o limite guardado
+        let restored = if probe_enabled {
+            None
+        } else {
+            rtt.probing.take().map(|probe| probe.saved_limit)
+        };
+
+        let old_limit = self.current_limit.load(Ordering::Relaxed);
+        let new_limit = restored.unwrap_or(old_limit).clamp(min_limit, max_limit);
+        // O `FlowGuard` registra a recarga com os limites antigo e novo
+        if new_limit != old_limit {
+            self.current_limit.store(new_limit, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
+impl VegasParams {
+    fn decreased(&self, limit: usize) -> usize {
+        (limit * 3 / 4).max(self.min_limit)
+    }
+}
+
+/// Explica a mudança de limite no span `limit_update` do `FlowGuard`, que
+/// emite o evento (sem guard em volta, não faz nada).
+fn record_reason(reason: &'static str, base_rtt: Duration, queue: Option<f64>) {
+    let span = tracing::Span::current();
+    span.record("reason", reason);
+    span.record("base_rtt_us", base_rtt.as_micros() as u64);
+    if let Some(queue) = queue {
+        span.record("queue", queue);
+    }
+}
+
+fn constant(value: f64) -> LimitFn {
+    Box::new(move |_| value)
+}
+
+/// Construtor de [`VegasStrategy`]. Criado por [`VegasStrategy::builder`].
+///
+/// Padrões: limite inicial 10, `min_limit` 1, `max_limit` 10x o inicial,
+/// RTT base inicial de 1s, `alpha` 2, `beta` 4, sem `threshold`, sem
+/// envelhecimento do RTT base e sem sondagem (com semente aleatória).
+pub struct VegasBuilder {
+    initial_limit: usize,
+    min_limit: usize,
+    max_limit: Option<usize>,
+    initial_base_rtt: Duration,
+    alpha: LimitFn,
+    beta: LimitFn,
+    threshold: Option<LimitFn>,
+    base_rtt_window: Option<usize>,
+    probe: Option<ProbeConfig>,
+    probe_seed: Option<u64>,
+}
+
+impl Default for VegasBuilder {
+    fn default() -> Self {
+        Self {
+            initial_limit: 10,
+            min_limit: 1,
+            max_limit: None,
+            initial_base_rtt: Duration::from_millis(1000),
+            alpha: constant(2.0),
+            beta: constant(4.0),
+            threshold: None,
+            base_rtt_window: None,
+            probe: None,
+            probe_seed: None,
+        }
+    }
+}
+
+impl VegasBuilder {
+    pub fn initial_limit(mut self, limit: usize) -> Self {
+        self.initial_limit = limit;
+        self
+    }
+
+    pub fn min_limit(mut self, limit: usize) -> Self {
+        self.min_limit = limit;
+        self
+    }
+
+    pub fn max_limit(mut self, limit: usize) -> Self {
+        self.max_limit = Some(limit);
+        self
+    }
+
+    /// RTT base usado até chegar uma amostra mais rápida.
+    pub fn initial_base_rtt(mut self, rtt: Duration) -> Self {
+        self.initial_base_rtt = rtt;
+        self
+    }
+
+    /// Fila estimada abaixo da qual o limite cresce (constante).
+    pub fn alpha(self, alpha: f64) -> Self {
+        self.alpha_fn(move |_| alpha)
+    }
+
+    /// Fila estimada abaixo da qual o limite cresce, em função do limite atual.
+    pub fn alpha_fn(mut self, alpha: impl Fn(usize) -> f64 + Send + Sync + 'static) -> Self {
+        self.alpha = Box::new(alpha);
+        self
+    }
+
+    /// Fila estimada acima da qual o limite diminui (constante).
+    pub fn beta(self, beta: f64) -> Self {
+        self.beta_fn(move |_| beta)
+    }
+
+    /// Fila estimada acima da qual o limite diminui, em função do limite atual.
+    pub fn beta_fn(mut self, beta: impl Fn(usize) -> f64 + Send + Sync + 'static) -> Self {
+        self.beta = Box::new(beta);
+        self
+    }
+
+    /// Fila estimada abaixo da qual o limite cresce `beta(limit)` de uma vez
+    /// em vez de 1 (constante).
+    pub fn threshold(self, threshold: f64) -> Self {
+        self.threshold_fn(move |_| threshold)
+    }
+
+    /// Fila estimada abaixo da qual o limite cresce `beta(limit)` de uma vez
+    /// em vez de 1, em função do limite atual.
+    pub fn threshold_fn(
+        mut self,
+        threshold: impl Fn(usize) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        self.threshold = Some(Box::new(threshold));
+        self
+    }
+
+    /// Envelhece o RTT base: a cada `samples` amostras ele passa a ser o menor
+    /// RTT visto nessa janela, então uma amostra "sortuda" ou uma mudança
+    /// permanente de latência deixam de travar o limite para sempre.
+    pub fn base_rtt_window(mut self, samples: usize) -> Self {
+        self.base_rtt_window = Some(samples);
+        self
+    }
+
+    /// Liga a sondagem periódica: a cada `samples` amostras (mais jitter) o
+    /// limite cai brevemente para medir de novo a latência sem carga.
+    pub fn probe_interval(mut self, samples: usize) -> Self {
+        self.probe_config().interval = samples;
+        self
+    }
+
+    /// Jitter do intervalo de sondagem, como fração do intervalo (padrão: 0.5,
+    /// ou seja, entre 1x e 1.5x `probe_interval`).
+    pub fn probe_jitter(mut self, jitter: f64) -> Self {
+        self.probe_config().jitter = jitter;
+        self
+    }
+
+    /// Limite usado durante a sondagem (padrão: `min_limit`).
+    pub fn probe_limit(mut self, limit: usize) -> Self {
+        self.probe_config().limit = Some(limit);
+        self
+    }
+
+    /// Quantas amostras a sondagem mede antes de restaurar o limite (padrão: 10).
+    pub fn probe_samples(mut self, samples: usize) -> Self {
+        self.probe_config().samples = samples;
+        self
+    }
+
+    /// Semente do sorteio do jitter. Com a mesma semente as sondagens caem nas
+    /// mesmas amostras, o que torna simulações e testes reproduzíveis (padrão:
+    /// semente aleatória).
+    pub fn probe_seed(mut self, seed: u64) -> Self {
+        self.probe_seed = Some(seed);
+        self
+    }
+
+    fn probe_config(&mut self) -> &mut ProbeConfig {
+        self.probe.get_or_insert(ProbeConfig {
+            interval: 1000,
+            jitter: 0.5,
+            limit: None,
+            samples: 10,
+        })
+    }
+
+    pub fn build(self) -> Result<VegasStrategy, ConfigError> {
+        let max_limit = self
+            .max_limit
+            .unwrap_or(self.initial_limit.saturating_mul(10));
+
+        if self.min_limit == 0 {
+            return Err(ConfigError::ZeroMinLimit);
+        }
+        if self.min_limit > max_limit {
+            return Err(ConfigError::MinAboveMax {
+                min: self.min_limit,
+                max: max_limit,
+            });
+        }
+        if self.initial_limit < self.min_limit || self.initial_limit > max_limit {
+            return Err(ConfigError::InitialOutOfBounds {
+                initial: self.initial_limit,
+                min: self.min_limit,
+                max: max_limit,
+            });
+        }
+        if self.initial_base_rtt.is_zero() {
+            return Err(ConfigError::ZeroBaseRtt);
+        }
+        if self.base_rtt_window == Some(0) {
+            return Err(invalid("base_rtt_window", "must be at least 1"));
+        }
+        if let Some(probe) = &self.probe {
+            if probe.interval == 0 {
+                return Err(invalid("probe_interval", "must be at least 1"));
+            }
+            if probe.samples == 0 {
+                return Err(invalid("probe_samples", "must be at least 1"));
+            }
+            if !(0.0..=1.0).contains(&probe.jitter) {
+                return Err(invalid("probe_jitter", "must be between 0.0 and 1.0"));
+            }
+            if probe.limit == Some(0) {
+                return Err(invalid("probe_limit", "must be at least 1"));
+            }
+        }
+
+        // As funções só podem ser checadas por amostragem: nos extremos e no
+        // valor inicial do limite
+        for limit in [self.min_limit, self.initial_limit, max_limit] {
+            let alpha = (self.alpha)(limit);
+            let beta = (self.beta)(limit);
+            check_threshold("alpha", alpha, limit)?;
+            check_threshold("beta", beta, limit)?;
+            if alpha > beta {
+                return Err(ConfigError::AlphaAboveBeta { alpha, beta, limit });
+            }
+            if let Some(threshold) = &self.threshold {
+                let threshold = threshold(limit);
+                check_threshold("threshold", threshold, limit)?;
+                if threshold > alpha {
+                    return Err(ConfigError::ThresholdAboveAlpha {
+                        threshold,
+                        alpha,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        Ok(VegasStrategy {
+            current_limit: AtomicUsize::new(self.initial_limit),
+            rtt: Mutex::new(RttState::new(
+                self.initial_base_rtt,
+                self.probe_seed.map_or_else(Rng::from_entropy, Rng::new),
+            )),
+            params: RwLock::new(VegasParams {
+                alpha: self.alpha,
+                beta: self.beta,
+                threshold: self.threshold,
+                min_limit: self.min_limit,
+                max_limit,
+                base_rtt_window: self.base_rtt_window,
+                probe: self.probe,
+            }),
+        })
+    }
+}
+
+fn invalid(name: &'static str, reason: &'static str) -> ConfigError {
+    ConfigError::InvalidParameter { name, reason }
+}
+
+fn check_threshold(name: &'static str, value: f64, limit: usize) -> Result<(), ConfigError> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidThreshold { name, value, limit })
     }
 }
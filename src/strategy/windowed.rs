@@ -0,0 +1,286 @@
+/*
+ * Created by: Cleiton Augusto Correa Bezerra
+ * Project: FlowGuard - Adaptive Backpressure for Rust
+ * Agregação de amostras em janelas
+ */
+
+#[cfg(feature = "serde")]
+use crate::config::StrategyConfig;
+#[cfg(feature = "serde")]
+use crate::error::ConfigError;
+use crate::LimitStrategy;
+#[cfg(feature = "serde")]
+use crate::ReloadableStrategy;
+use parking_lot::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Buckets por oitava do histograma (resolução de ~19% por bucket).
+const BUCKETS_PER_OCTAVE: f64 = 4.0;
+/// 4 buckets por oitava a partir de 1µs cobrem até ~18 minutos.
+const BUCKETS: usize = 128;
+
+/// Como as latências de uma janela viram uma única amostra.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Aggregation {
+    /// Menor latência da janela.
+    Min,
+    /// Média das latências da janela.
+    Average,
+    /// Percentil (entre 0.0 e 100.0) estimado por um histograma logarítmico.
+    Percentile(f64),
+}
+
+/// Envolve outra estratégia e entrega a ela uma amostra agregada por janela
+/// em vez de uma por requisição.
+///
+/// A janela fecha ao atingir `max_samples` amostras ou, se configurado,
+/// `max_duration` desde a primeira amostra. Ao fechar:
+///
+/// - se a fração de erros da janela passou de `error_threshold` (padrão:
+///   10%), a estratégia interna recebe um único `on_error()`;
+/// - senão, recebe `on_success()` com a latência agregada dos sucessos.
+///
+/// Um outlier isolado, lento ou com erro, deixa de mover o limite e a
+/// estratégia interna (com seus locks e atômicos) só é tocada uma vez por
+/// janela.
+///
+/// A janela só é avaliada quando chega uma amostra: com `max_duration`, a
+/// última janela parcial antes de o tráfego parar fica pendente até a próxima
+/// requisição, que a fecha (e abre outra) ao ser medida.
+pub struct WindowedStrategy<S> {
+    inner: S,
+    window: Mutex<Window>,
+    /// Trocados por inteiro na recarga a quente (`update_config`).
+    params: RwLock<WindowParams>,
+}
+
+#[derive(Clone, Copy)]
+struct WindowParams {
+    max_samples: usize,
+    max_duration: Option<Duration>,
+    aggregation: Aggregation,
+    error_threshold: f64,
+}
+
+struct Window {
+    started: Option<Instant>,
+    count: usize,
+    errors: usize,
+    /// Maior número de execuções em andamento visto na janela.
+    in_flight: usize,
+    sum: Duration,
+    min: Duration,
+    histogram: [u32; BUCKETS],
+}
+
+impl Window {
+    fn new() -> Self {
+        Self {
+            started: None,
+            count: 0,
+            errors: 0,
+            in_flight: 0,
+            sum: Duration::ZERO,
+            min: Duration::MAX,
+            histogram: [0; BUCKETS],
+        }
+    }
+
+    fn successes(&self) -> usize {
+        self.count - self.errors
+    }
+
+    /// Latência agregada dos sucessos; só chamada com pelo menos um.
+    fn aggregate(&self, aggregation: Aggregation) -> Duration {
+        match aggregation {
+            Aggregation::Min => self.min,
+            Aggregation::Average => self.sum / self.successes() as u32,
+            Aggregation::Percentile(percentile) => self.percentile(percentile),
+        }
+    }
+
+    fn percentile(&self, percentile: f64) -> Duration {
+        let rank = ((percentile / 100.0) * self.successes() as f64)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0u64;
+        for (bucket, &hits) in self.histogram.iter().enumerate() {
+            seen += u64::from(hits);
+            if seen >= rank {
+                return bucket_value(bucket);
+            }
+        }
+        bucket_value(BUCKETS - 1)
+    }
+}
+
+fn bucket_index(latency: Duration) -> usize {
+    let micros = latency.as_secs_f64() * 1_000_000.0;
+    if micros <= 1.0 {
+        return 0;
+    }
+    ((micros.log2() * BUCKETS_PER_OCTAVE) as usize).min(BUCKETS - 1)
+}
+
+/// Ponto médio (geométrico) do bucket.
+fn bucket_value(bucket: usize) -> Duration {
+    let micros = 2f64.powf((bucket as f64 + 0.5) / BUCKETS_PER_OCTAVE);
+    Duration::from_secs_f64(micros / 1_000_000.0)
+}
+
+impl<S: LimitStrategy> WindowedStrategy<S> {
+    /// Janela de 100 amostras, agregadas pela média.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            window: Mutex::new(Window::new()),
+            params: RwLock::new(WindowParams {
+                max_samples: 100,
+                max_duration: None,
+                aggregation: Aggregation::Average,
+                error_threshold: 0.1,
+            }),
+        }
+    }
+
+    /// Fecha a janela depois de `samples` amostras.
+    pub fn with_sample_window(mut self, samples: usize) -> Self {
+        self.params.get_mut().max_samples = samples.max(1);
+        self
+    }
+
+    /// Fecha a janela quando `duration` tiver passado desde a primeira
+    /// amostra, mesmo sem atingir o número de amostras.
+    pub fn with_time_window(mut self, duration: Duration) -> Self {
+        self.params.get_mut().max_duration = Some(duration);
+        self
+    }
+
+    /// # Panics
+    ///
+    /// Se for `Aggregation::Percentile` fora do intervalo `[0.0, 100.0]`.
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        if let Aggregation::Percentile(percentile) = aggregation {
+            assert!(
+                (0.0..=100.0).contains(&percentile),
+                "percentile must be between 0.0 and 100.0, got {percentile}"
+            );
+        }
+        self.params.get_mut().aggregation = aggregation;
+        self
+    }
+
+    /// Fração de erros da janela acima da qual a estratégia interna recebe
+    /// `on_error()` em vez da latência agregada (padrão: 0.1). Com 0.0
+    /// qualquer erro na janela conta.
+    ///
+    /// # Panics
+    ///
+    /// Se `ratio` não estiver no intervalo `[0.0, 1.0)`.
+    pub fn with_error_threshold(mut self, ratio: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&ratio),
+            "error_threshold must be between 0.0 and 1.0 (exclusive), got {ratio}"
+        );
+        self.params.get_mut().error_threshold = ratio;
+        self
+    }
+
+    /// A estratégia envolvida.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn push(&self, sample: Option<Duration>, in_flight: usize) {
+        let params = *self.params.read();
+        let closed = {
+            let mut window = self.window.lock();
+            let now = Instant::now();
+            let started = *window.started.get_or_insert(now);
+
+            window.count += 1;
+            window.in_flight = window.in_flight.max(in_flight);
+            match sample {
+                Some(latency) => {
+                    window.sum += latency;
+                    window.min = window.min.min(latency);
+                    window.histogram[bucket_index(latency)] += 1;
+                }
+                None => window.errors += 1,
+            }
+
+            let full = window.count >= params.max_samples;
+            let expired = params
+                .max_duration
+                .is_some_and(|max| now.duration_since(started) >= max);
+
+            if full || expired {
+                Some(std::mem::replace(&mut *window, Window::new()))
+            } else {
+                None
+            }
+        };
+
+        // A estratégia interna é chamada fora do lock da janela
+        if let Some(window) = closed {
+            let error_ratio = window.errors as f64 / window.count as f64;
+            if error_ratio > params.error_threshold || window.successes() == 0 {
+                self.inner.on_error();
+            } else {
+                self.inner.on_success_with_in_flight(
+                    window.aggregate(params.aggregation),
+                    window.in_flight,
+                );
+            }
+        }
+    }
+}
+
+impl<S: LimitStrategy> LimitStrategy for WindowedStrategy<S> {
+    fn current_limit(&self) -> usize {
+        self.inner.current_limit()
+    }
+
+    fn on_success(&self, latency: Duration) {
+        self.push(Some(latency), usize::MAX);
+    }
+
+    fn on_error(&self) {
+        self.push(None, 0);
+    }
+
+    /// A amostra agregada leva o maior `in_flight` da janela.
+    fn on_success_with_in_flight(&self, latency: Duration, in_flight: usize) {
+        self.push(Some(latency), in_flight);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: ReloadableStrategy> ReloadableStrategy for WindowedStrategy<S> {
+    /// Troca a janela e a agregação e repassa `inner` à estratégia envolvida.
+    /// A janela em andamento fecha pelas regras novas.
+    fn update_config(&self, config: &StrategyConfig) -> Result<(), ConfigError> {
+        let StrategyConfig::Windowed(config) = config else {
+            return Err(ConfigError::StrategyMismatch {
+                expected: "windowed",
+                found: config.kind(),
+            });
+        };
+
+        config.validate()?;
+        self.inner.update_config(&config.inner)?;
+        *self.params.write() = WindowParams {
+            max_samples: config.samples,
+            max_duration: config.duration,
+            aggregation: config.aggregation,
+            error_threshold: config.error_threshold,
+        };
+        Ok(())
+    }
+}
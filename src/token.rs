@@ -0,0 +1,79 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Token RAII de medição
+ */
+
+use crate::limiter::FlowGuard;
+use crate::semaphore::DynamicPermit;
+use crate::LimitStrategy;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Como uma execução deve ser contabilizada pela estratégia de limite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Execução saudável: a latência é repassada para `on_success`.
+    Success,
+    /// Sinal de sobrecarga: a estratégia reduz o limite via `on_error`.
+    Dropped,
+    /// Não diz nada sobre a saúde do sistema: a estratégia não é informada.
+    Ignore,
+}
+
+/// Permissão de execução obtida com `FlowGuard::acquire()`.
+///
+/// Guarda a permissão e o instante de início. O resultado deve ser informado
+/// com [`success`](Self::success), [`dropped`](Self::dropped) ou
+/// [`ignore`](Self::ignore); se o token for descartado sem isso, conta como o
+/// resultado padrão do guard (`FlowGuard::with_default_outcome`). Em todos os
+/// casos a permissão volta para o semáforo.
+pub struct FlowToken<S: LimitStrategy + 'static> {
+    guard: FlowGuard<S>,
+    permit: Option<DynamicPermit>,
+    start: Instant,
+}
+
+impl<S: LimitStrategy + 'static> FlowToken<S> {
+    pub(crate) fn new(guard: FlowGuard<S>, permit: DynamicPermit) -> Self {
+        Self {
+            guard,
+            permit: Some(permit),
+            start: Instant::now(),
+        }
+    }
+
+    /// Tempo decorrido desde a aquisição da permissão.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Registra a execução como bem-sucedida.
+    pub fn success(mut self) {
+        self.finish(Outcome::Success);
+    }
+
+    /// Registra a execução como sobrecarga (reduz o limite).
+    pub fn dropped(mut self) {
+        self.finish(Outcome::Dropped);
+    }
+
+    /// Libera a permissão sem informar a estratégia.
+    pub fn ignore(mut self) {
+        self.finish(Outcome::Ignore);
+    }
+
+    fn finish(&mut self, outcome: Outcome) {
+        if let Some(permit) = self.permit.take() {
+            self.guard.record(outcome, self.start.elapsed());
+            // Libera só depois de atualizar o limite, como em `FlowGuard::run`
+            drop(permit);
+            self.guard.publish_gauges();
+        }
+    }
+}
+
+impl<S: LimitStrategy + 'static> Drop for FlowToken<S> {
+    fn drop(&mut self) {
+        let outcome = self.guard.default_outcome();
+        self.finish(outcome);
+    }
+}
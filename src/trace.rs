@@ -0,0 +1,275 @@
+/* * Created and Developed by: Cleiton Augusto Correa Bezerra
+ * FlowGuard - Gravação e replay de amostras de produção
+ */
+
+//! Gravação de amostras reais (`FlowGuard::with_trace_recorder`) e replay
+//! offline através de qualquer `LimitStrategy`.
+//!
+//! O formato é CSV com cabeçalho, uma amostra por linha:
+//!
+//! ```text
+//! timestamp_us,latency_us,outcome,in_flight
+//! 1042,18250,success,12
+//! 1310,250031,dropped,13
+//! ```
+//!
+//! O replay é em malha aberta: o limite calculado não altera o tráfego, então
+//! a linha do tempo mostra o que a estratégia teria decidido diante da mesma
+//! sequência de latências. Ele roda com o relógio do tokio pausado e avançado
+//! até o `timestamp` de cada amostra, então o estado que depende de tempo
+//! (sondagem do Vegas, janelas por tempo do `WindowedStrategy`) segue o tempo
+//! do trace, não o do processo que faz o replay.
+//!
+//! ```
+//! use flow_guard::trace::{read_trace, replay};
+//! use flow_guard::AimdStrategy;
+//!
+//! let csv = "timestamp_us,latency_us,outcome,in_flight\n\
+//!            0,20000,success,1\n\
+//!            1000,20000,success,2\n\
+//!            2000,90000,dropped,3\n";
+//! let samples = read_trace(csv.as_bytes()).unwrap();
+//!
+//! // AIMD sobe para 12 com os sucessos e recua para 10 no descarte
+//! let timeline = replay(&AimdStrategy::new(10), &samples);
+//! let limits: Vec<usize> = timeline.iter().map(|point| point.limit).collect();
+//! assert_eq!(limits, [10, 11, 12, 10]);
+//! ```
+
+use crate::error::TraceError;
+use crate::token::Outcome;
+use crate::LimitStrategy;
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+
+const HEADER: &str = "timestamp_us,latency_us,outcome,in_flight";
+
+/// Uma execução medida pelo guard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceSample {
+    /// Instante da medição, relativo ao início da gravação.
+    pub timestamp: Duration,
+    pub latency: Duration,
+    pub outcome: Outcome,
+    /// Execuções segurando permissão no momento da medição (incluindo esta).
+    pub in_flight: usize,
+}
+
+/// Grava as amostras de um ou mais guards em CSV.
+///
+/// As escritas passam por um buffer; chame [`flush`](Self::flush) antes de
+/// ler o arquivo. Erros de escrita são registrados com `tracing::warn!` uma
+/// única vez e as amostras seguintes são descartadas.
+pub struct TraceRecorder {
+    writer: Mutex<Box<dyn Write + Send>>,
+    start: Instant,
+    failed: AtomicBool,
+}
+
+impl TraceRecorder {
+    /// Grava em `writer`, começando pelo cabeçalho.
+    pub fn new(writer: impl Write + Send + 'static) -> io::Result<Self> {
+        let mut writer: Box<dyn Write + Send> = Box::new(BufWriter::new(writer));
+        writeln!(writer, "{HEADER}")?;
+        Ok(Self {
+            writer: Mutex::new(writer),
+            start: Instant::now(),
+            failed: AtomicBool::new(false),
+        })
+    }
+
+    /// Cria (ou sobrescreve) o arquivo em `path`.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(File::create(path)?)
+    }
+
+    pub(crate) fn record(&self, latency: Duration, outcome: Outcome, in_flight: usize) {
+        self.write(TraceSample {
+            timestamp: self.start.elapsed(),
+            latency,
+            outcome,
+            in_flight,
+        });
+    }
+
+    /// Grava uma amostra.
+    pub fn write(&self, sample: TraceSample) {
+        if self.failed.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let result = writeln!(
+            self.writer.lock(),
+            "{},{},{},{}",
+            sample.timestamp.as_micros(),
+            sample.latency.as_micros(),
+            outcome_name(sample.outcome),
+            sample.in_flight
+        );
+        if let Err(err) = result {
+            self.fail(err);
+        }
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.writer.lock().flush()
+    }
+
+    fn fail(&self, err: io::Error) {
+        if !self.failed.swap(true, Ordering::Relaxed) {
+            tracing::warn!(error = %err, "trace recorder failed, dropping further samples");
+        }
+    }
+}
+
+impl Drop for TraceRecorder {
+    fn drop(&mut self) {
+        let _ = self.writer.get_mut().flush();
+    }
+}
+
+fn outcome_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Success => "success",
+        Outcome::Dropped => "dropped",
+        Outcome::Ignore => "ignore",
+    }
+}
+
+/// Lê um trace em CSV. O cabeçalho é opcional.
+pub fn read_trace(reader: impl BufRead) -> Result<Vec<TraceSample>, TraceError> {
+    let mut samples = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line == HEADER {
+            continue;
+        }
+        samples.push(parse_sample(line).map_err(|reason| TraceError::Parse {
+            line: index + 1,
+            reason,
+        })?);
+    }
+
+    Ok(samples)
+}
+
+fn parse_sample(line: &str) -> Result<TraceSample, String> {
+    let fields: Vec<&str> = line.split(',').collect();
+    let [timestamp, latency, outcome, in_flight] = fields[..] else {
+        return Err(format!("expected 4 fields, found {}", fields.len()));
+    };
+
+    let micros = |name: &str, value: &str| {
+        value
+            .trim()
+            .parse::<u64>()
+            .map(Duration::from_micros)
+            .map_err(|err| format!("{name}: {err}"))
+    };
+
+    Ok(TraceSample {
+        timestamp: micros("timestamp_us", timestamp)?,
+        latency: micros("latency_us", latency)?,
+        outcome: match outcome.trim() {
+            "success" => Outcome::Success,
+            "dropped" => Outcome::Dropped,
+            "ignore" => Outcome::Ignore,
+            other => return Err(format!("unknown outcome {other:?}")),
+        },
+        in_flight: in_flight
+            .trim()
+            .parse()
+            .map_err(|err| format!("in_flight: {err}"))?,
+    })
+}
+
+/// Ponto da linha do tempo produzida por [`replay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitPoint {
+    pub timestamp: Duration,
+    /// Limite da estratégia depois desta amostra.
+    pub limit: usize,
+    /// Execuções em andamento registradas no trace neste instante. Acima de
+    /// `limit`, a estratégia teria segurado parte delas.
+    pub in_flight: usize,
+}
+
+/// Alimenta `strategy` com as amostras, na ordem, e devolve um ponto para o
+/// estado inicial e outro a cada mudança de limite.
+///
+/// Antes de cada amostra o relógio pausado avança até o `timestamp` dela
+/// (nunca volta, se o trace tiver instantes fora de ordem). O replay usa um
+/// runtime próprio em outra thread, então pode ser chamado de dentro ou de
+/// fora de um runtime do tokio.
+///
+/// Amostras `Ignore` não são repassadas, como no `FlowGuard`.
+pub fn replay<'a, S>(
+    strategy: &S,
+    samples: impl IntoIterator<Item = &'a TraceSample>,
+) -> Vec<LimitPoint>
+where
+    S: LimitStrategy + ?Sized,
+{
+    let samples: Vec<&TraceSample> = samples.into_iter().collect();
+
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_time()
+                    .start_paused(true)
+                    .build()
+                    .expect("failed to build replay runtime")
+                    .block_on(replay_samples(strategy, &samples))
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+async fn replay_samples<S>(strategy: &S, samples: &[&TraceSample]) -> Vec<LimitPoint>
+where
+    S: LimitStrategy + ?Sized,
+{
+    let start = Instant::now();
+    let mut limit = strategy.current_limit();
+    let mut timeline = vec![LimitPoint {
+        timestamp: Duration::ZERO,
+        limit,
+        in_flight: 0,
+    }];
+
+    for sample in samples {
+        let elapsed = start.elapsed();
+        if sample.timestamp > elapsed {
+            tokio::time::advance(sample.timestamp - elapsed).await;
+        }
+
+        match sample.outcome {
+            Outcome::Success => {
+                strategy.on_success_with_in_flight(sample.latency, sample.in_flight)
+            }
+            Outcome::Dropped => strategy.on_error(),
+            Outcome::Ignore => continue,
+        }
+
+        let new_limit = strategy.current_limit();
+        if new_limit != limit {
+            limit = new_limit;
+            timeline.push(LimitPoint {
+                timestamp: sample.timestamp,
+                limit,
+                in_flight: sample.in_flight,
+            });
+        }
+    }
+
+    timeline
+}
@@ -0,0 +1,82 @@
+//! Estratégias de apoio compartilhadas pelos testes de integração.
+
+#![allow(dead_code)]
+
+use flow_guard::LimitStrategy;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Estratégia cujo limite é controlado pelo próprio teste; cada erro reduz o
+/// limite em um (sem passar de 1).
+pub struct ManualLimit(AtomicUsize);
+
+impl ManualLimit {
+    pub fn new(limit: usize) -> Self {
+        Self(AtomicUsize::new(limit))
+    }
+
+    pub fn set(&self, limit: usize) {
+        self.0.store(limit, Ordering::SeqCst);
+    }
+}
+
+impl LimitStrategy for ManualLimit {
+    fn current_limit(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn on_success(&self, _latency: Duration) {}
+
+    fn on_error(&self) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |limit| {
+                Some(limit.saturating_sub(1).max(1))
+            });
+    }
+}
+
+/// Limite fixo que registra as latências e os erros recebidos.
+pub struct Recorder {
+    limit: usize,
+    latencies: Mutex<Vec<Duration>>,
+    errors: AtomicUsize,
+}
+
+impl Recorder {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            latencies: Mutex::default(),
+            errors: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn latencies(&self) -> Vec<Duration> {
+        self.latencies.lock().clone()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::SeqCst)
+    }
+
+    /// `(sucessos, erros)` recebidos até agora.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.latencies.lock().len(), self.errors())
+    }
+}
+
+impl LimitStrategy for Recorder {
+    fn current_limit(&self) -> usize {
+        self.limit
+    }
+
+    fn on_success(&self, latency: Duration) {
+        self.latencies.lock().push(latency);
+    }
+
+    fn on_error(&self) {
+        self.errors.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,105 @@
+//! `AimdStrategy`: crescimento aditivo, corte multiplicativo.
+
+use flow_guard::{AimdStrategy, FlowGuard, LimitStrategy};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn grows_additively_on_success() {
+    let strategy = AimdStrategy::new(10).with_increase(2);
+
+    for _ in 0..5 {
+        strategy.on_success(Duration::from_millis(10));
+    }
+
+    assert_eq!(strategy.current_limit(), 20);
+}
+
+#[test]
+fn growth_stops_at_max_limit() {
+    let strategy = AimdStrategy::new(10).with_max_limit(12);
+
+    for _ in 0..100 {
+        strategy.on_success(Duration::from_millis(10));
+    }
+
+    assert_eq!(strategy.current_limit(), 12);
+}
+
+#[test]
+fn cuts_multiplicatively_on_error() {
+    let strategy = AimdStrategy::new(100).with_backoff_ratio(0.5);
+
+    strategy.on_error();
+    assert_eq!(strategy.current_limit(), 50);
+
+    strategy.on_error();
+    assert_eq!(strategy.current_limit(), 25);
+}
+
+#[test]
+fn slow_success_counts_as_error() {
+    let strategy = AimdStrategy::new(100)
+        .with_backoff_ratio(0.8)
+        .with_timeout(Duration::from_millis(200));
+
+    strategy.on_success(Duration::from_millis(199));
+    assert_eq!(strategy.current_limit(), 101);
+
+    strategy.on_success(Duration::from_millis(500));
+    assert_eq!(strategy.current_limit(), 80);
+}
+
+#[test]
+fn never_goes_below_min_limit() {
+    let strategy = AimdStrategy::new(10).with_min_limit(4);
+
+    for _ in 0..50 {
+        strategy.on_error();
+    }
+
+    assert_eq!(strategy.current_limit(), 4);
+}
+
+#[test]
+#[should_panic(expected = "backoff_ratio")]
+fn rejects_invalid_backoff_ratio() {
+    let _ = AimdStrategy::new(10).with_backoff_ratio(1.5);
+}
+
+#[tokio::test]
+async fn drives_flow_guard_limit() {
+    let strategy = Arc::new(AimdStrategy::new(5).with_backoff_ratio(0.5));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    for _ in 0..5 {
+        guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    }
+    assert_eq!(guard.current_limit(), 10);
+    assert_eq!(guard.available_permits(), 10);
+
+    let _ = guard.run(async { Err::<(), _>("timeout") }).await;
+    assert_eq!(guard.current_limit(), 5);
+    assert_eq!(guard.available_permits(), 5);
+}
+
+#[test]
+fn initial_limit_is_clamped_to_bounds() {
+    let strategy = AimdStrategy::new(50).with_max_limit(20);
+    assert_eq!(strategy.current_limit(), 20);
+
+    let strategy = AimdStrategy::new(2).with_min_limit(5);
+    assert_eq!(strategy.current_limit(), 5);
+}
+
+#[test]
+#[should_panic(expected = "min_limit")]
+fn rejects_zero_min_limit() {
+    let _ = AimdStrategy::new(10).with_min_limit(0);
+}
+
+#[test]
+#[should_panic(expected = "initial_limit")]
+fn rejects_zero_initial_limit() {
+    let _ = AimdStrategy::new(0);
+}
@@ -0,0 +1,120 @@
+//! Fila de espera limitada: tamanho máximo e tempo máximo de espera.
+
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{sleep, Instant};
+
+/// Ocupa a única permissão do guard até o sender ser usado.
+fn hold_permit(
+    guard: &Arc<FlowGuard<FixedStrategy>>,
+) -> (oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+    let guard = Arc::clone(guard);
+    let handle = tokio::spawn(async move {
+        guard
+            .run(async move {
+                let _ = rx.await;
+                Ok::<_, &str>(())
+            })
+            .await
+            .unwrap();
+    });
+    (tx, handle)
+}
+
+#[tokio::test(start_paused = true)]
+async fn overflowing_the_queue_fails_with_queue_full() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)).with_max_queue(2));
+    let (tx, holder) = hold_permit(&guard);
+    sleep(Duration::from_millis(10)).await;
+
+    let queued: Vec<_> = (0..2)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move { guard.run(async { Ok::<_, &str>(()) }).await })
+        })
+        .collect();
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(guard.queue_len(), 2);
+
+    let overflow = guard.run(async { Ok::<_, &str>(()) }).await;
+    assert!(matches!(overflow, Err(FlowError::QueueFull)));
+    assert_eq!(guard.queue_len(), 2);
+
+    tx.send(()).unwrap();
+    holder.await.unwrap();
+    for task in queued {
+        assert!(task.await.unwrap().is_ok());
+    }
+    assert_eq!(guard.queue_len(), 0);
+    assert_eq!(guard.available_permits(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_too_long_fails_with_queue_timeout() {
+    let guard = Arc::new(
+        FlowGuard::new(FixedStrategy::new(1)).with_queue_timeout(Duration::from_millis(30)),
+    );
+    let (tx, holder) = hold_permit(&guard);
+    sleep(Duration::from_millis(10)).await;
+
+    let started = Instant::now();
+    let result = guard.run(async { Ok::<_, &str>(()) }).await;
+    assert!(matches!(result, Err(FlowError::QueueTimeout)));
+    assert!(started.elapsed() >= Duration::from_millis(30));
+    assert_eq!(guard.queue_len(), 0);
+
+    tx.send(()).unwrap();
+    holder.await.unwrap();
+
+    // Sem contenção, a execução passa direto sem entrar na fila
+    assert!(guard.run(async { Ok::<_, &str>(()) }).await.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiter_served_before_timeout_succeeds() {
+    let guard = Arc::new(
+        FlowGuard::new(FixedStrategy::new(1)).with_queue_timeout(Duration::from_millis(500)),
+    );
+    let (tx, holder) = hold_permit(&guard);
+    sleep(Duration::from_millis(10)).await;
+
+    let waiter = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move { guard.run(async { Ok::<_, &str>("servido") }).await })
+    };
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(guard.queue_len(), 1);
+
+    tx.send(()).unwrap();
+    holder.await.unwrap();
+    assert_eq!(waiter.await.unwrap().unwrap(), "servido");
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelled_waiters_leave_the_queue() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)).with_max_queue(3));
+    let (tx, holder) = hold_permit(&guard);
+    sleep(Duration::from_millis(10)).await;
+
+    let waiters: Vec<_> = (0..3)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move { guard.run(async { Ok::<_, &str>(()) }).await })
+        })
+        .collect();
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(guard.queue_len(), 3);
+
+    for waiter in &waiters {
+        waiter.abort();
+    }
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(guard.queue_len(), 0);
+
+    tx.send(()).unwrap();
+    holder.await.unwrap();
+    assert_eq!(guard.available_permits(), 1);
+}
@@ -0,0 +1,125 @@
+//! Classificação de resultados: nem todo `Err` é sobrecarga, nem todo `Ok` é saudável.
+
+mod common;
+
+use common::Recorder;
+use flow_guard::{Classifier, DefaultClassifier, FlowGuard, Outcome};
+use std::sync::Arc;
+
+#[derive(Debug)]
+enum ApiError {
+    NotFound,
+    Timeout,
+}
+
+#[test]
+fn default_classifier_keeps_previous_behavior() {
+    let ok: Result<(), &str> = Ok(());
+    let err: Result<(), &str> = Err("falha");
+
+    assert_eq!(DefaultClassifier.classify(&ok), Outcome::Success);
+    assert_eq!(DefaultClassifier.classify(&err), Outcome::Dropped);
+}
+
+#[tokio::test]
+async fn run_keeps_treating_every_err_as_overload() {
+    let strategy = Arc::new(Recorder::new(4));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    let _ = guard.run(async { Ok::<_, ApiError>(()) }).await;
+    let _ = guard.run(async { Err::<(), _>(ApiError::NotFound) }).await;
+
+    assert_eq!(strategy.counts(), (1, 1));
+}
+
+#[tokio::test]
+async fn closure_classifier_ignores_client_errors() {
+    let strategy = Arc::new(Recorder::new(4));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    let classifier = |result: &Result<(), ApiError>| match result {
+        Ok(_) => Outcome::Success,
+        Err(ApiError::NotFound) => Outcome::Ignore,
+        Err(ApiError::Timeout) => Outcome::Dropped,
+    };
+
+    for _ in 0..10 {
+        let result = guard
+            .run_with(&classifier, async { Err::<(), _>(ApiError::NotFound) })
+            .await;
+        assert!(result.is_err());
+    }
+    assert_eq!(strategy.counts(), (0, 0));
+
+    let _ = guard
+        .try_run_with(&classifier, async { Err::<(), _>(ApiError::Timeout) })
+        .await;
+    assert_eq!(strategy.counts(), (0, 1));
+    assert_eq!(guard.available_permits(), 4);
+}
+
+#[tokio::test]
+async fn ok_result_can_count_as_overload() {
+    let strategy = Arc::new(Recorder::new(4));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    // Um "Ok" que carrega um 503 do downstream
+    let classifier = |result: &Result<u16, ()>| match result {
+        Ok(status) if *status >= 500 => Outcome::Dropped,
+        Ok(_) => Outcome::Success,
+        Err(_) => Outcome::Dropped,
+    };
+
+    let status = guard
+        .run_with(&classifier, async { Ok(503) })
+        .await
+        .unwrap();
+
+    assert_eq!(status, 503);
+    assert_eq!(strategy.counts(), (0, 1));
+}
+
+#[cfg(all(feature = "axum", feature = "tower"))]
+#[tokio::test]
+async fn http_classifier_in_layer_maps_status_codes() {
+    use axum::{
+        error_handling::HandleErrorLayer, http::StatusCode, response::IntoResponse, routing::get,
+        Router,
+    };
+    use flow_guard::{FlowError, FlowGuardLayer, HttpClassifier};
+    use tower::{ServiceBuilder, ServiceExt};
+
+    let strategy = Arc::new(Recorder::new(4));
+    let layer = FlowGuardLayer::new(Arc::clone(&strategy)).with_classifier(HttpClassifier);
+
+    let app = Router::new()
+        .route("/ok", get(|| async { StatusCode::OK }))
+        .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+        .route("/busy", get(|| async { StatusCode::TOO_MANY_REQUESTS }))
+        .route("/down", get(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(
+                    |err: FlowError<std::convert::Infallible>| async move { err.into_response() },
+                ))
+                .layer(layer),
+        );
+
+    for (path, expected) in [
+        ("/ok", StatusCode::OK),
+        ("/missing", StatusCode::NOT_FOUND),
+        ("/missing", StatusCode::NOT_FOUND),
+        ("/busy", StatusCode::TOO_MANY_REQUESTS),
+        ("/down", StatusCode::SERVICE_UNAVAILABLE),
+    ] {
+        let request = axum::http::Request::builder()
+            .uri(path)
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), expected);
+    }
+
+    // 200 -> sucesso; 404 -> ignorado; 429 e 503 -> sobrecarga
+    assert_eq!(strategy.counts(), (1, 2));
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "cli")]
+//! Binário `flowguard` (feature `cli`).
+
+use std::process::{Command, Output};
+
+fn flowguard(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_flowguard"))
+        .args(args)
+        .output()
+        .expect("failed to run flowguard")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "flowguard failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn simulate_prints_a_report() {
+    let output = flowguard(&[
+        "simulate",
+        "--strategy",
+        "aimd",
+        "--capacity",
+        "20",
+        "--latency",
+        "20ms",
+        "--duration",
+        "2s",
+        "--show-limits",
+    ]);
+    let text = stdout(&output);
+
+    assert!(text.contains("strategy     aimd"), "{text}");
+    assert!(text.contains("goodput"), "{text}");
+    assert!(text.contains("time_ms"), "{text}");
+}
+
+#[test]
+fn compare_prints_one_row_per_strategy() {
+    let output = flowguard(&[
+        "compare",
+        "--capacity",
+        "20",
+        "--latency",
+        "20ms",
+        "--duration",
+        "2s",
+    ]);
+    let text = stdout(&output);
+
+    let rows: Vec<&str> = text.lines().skip(1).collect();
+    assert_eq!(rows.len(), 3, "{text}");
+    for (row, name) in rows.iter().zip(["vegas", "aimd", "gradient"]) {
+        assert!(row.starts_with(name), "{text}");
+    }
+}
+
+#[test]
+fn compare_over_http() {
+    let output = flowguard(&[
+        "compare",
+        "--http",
+        "--strategies",
+        "aimd",
+        "--capacity",
+        "10",
+        "--latency",
+        "5ms",
+        "--rate",
+        "200",
+        "--duration",
+        "500ms",
+    ]);
+    let text = stdout(&output);
+
+    let row = text.lines().nth(1).expect("missing aimd row");
+    let goodput: f64 = row.split_whitespace().nth(1).unwrap().parse().unwrap();
+    assert!(goodput > 0.0, "{text}");
+}
+
+#[test]
+fn replay_prints_the_limit_timeline() {
+    let path = std::env::temp_dir().join(format!("flowguard-cli-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "timestamp_us,latency_us,outcome,in_flight\n\
+         0,20000,success,1\n\
+         1000,20000,success,2\n\
+         2000,90000,dropped,3\n",
+    )
+    .unwrap();
+
+    let output = flowguard(&["replay", path.to_str().unwrap(), "--strategy", "aimd"]);
+    std::fs::remove_file(&path).unwrap();
+    let text = stdout(&output);
+
+    assert!(
+        text.contains("3 samples, 3 changes, limit 10..12, final 10"),
+        "{text}"
+    );
+}
+
+#[test]
+fn invalid_arguments_fail() {
+    let output = flowguard(&["simulate", "--latency", "10"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing unit"));
+
+    let output = flowguard(&["replay", "/nonexistent/trace.csv"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("/nonexistent/trace.csv"));
+}
+
+#[test]
+fn degenerate_scenarios_are_rejected() {
+    for (args, message) in [
+        (["--capacity", "0"], "at least 1"),
+        (["--initial-limit", "0"], "at least 1"),
+        (["--latency", "0ms"], "greater than zero"),
+        (["--rate", "0"], "greater than zero"),
+        (["--rate", "inf"], "greater than zero"),
+        (["--failure-rate", "5"], "between 0 and 1"),
+        (["--failure-rate", "NaN"], "between 0 and 1"),
+    ] {
+        let output = flowguard(&[&["simulate"], &args[..]].concat());
+        assert!(!output.status.success(), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{args:?}: {stderr}");
+    }
+
+    let output = flowguard(&["replay", "trace.csv", "--initial-limit", "0"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("at least 1"));
+}
@@ -0,0 +1,106 @@
+//! `FlowGuard::close()` e `FlowGuard::drain()` para desligamento gracioso.
+
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{sleep, timeout};
+
+#[tokio::test]
+async fn closed_guard_rejects_new_runs() {
+    let guard = FlowGuard::new(FixedStrategy::new(4));
+    guard.close();
+
+    assert!(guard.is_closed());
+    assert!(matches!(
+        guard.run(async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::Closed)
+    ));
+    assert!(matches!(
+        guard.try_run(async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::Closed)
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn close_wakes_every_waiter_with_closed() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)));
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let holder = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move {
+            guard
+                .run(async move {
+                    let _ = rx.await;
+                    Ok::<_, &str>("terminou")
+                })
+                .await
+        })
+    };
+    sleep(Duration::from_millis(10)).await;
+
+    let waiters: Vec<_> = (0..10)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move { guard.run(async { Ok::<_, &str>("não deveria") }).await })
+        })
+        .collect();
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(guard.queue_len(), 10);
+
+    guard.close();
+
+    for waiter in waiters {
+        let result = timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("waiter não foi acordado pelo close()")
+            .unwrap();
+        assert!(matches!(result, Err(FlowError::Closed)));
+    }
+    assert_eq!(guard.queue_len(), 0);
+
+    // Quem já tinha permissão termina normalmente
+    tx.send(()).unwrap();
+    assert_eq!(holder.await.unwrap().unwrap(), "terminou");
+}
+
+#[tokio::test(start_paused = true)]
+async fn drain_resolves_once_in_flight_work_finishes() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(4)));
+
+    let workers: Vec<_> = (0..4)
+        .map(|i| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move {
+                guard
+                    .run(async move {
+                        sleep(Duration::from_millis(20 * (i + 1))).await;
+                        Ok::<_, &str>(())
+                    })
+                    .await
+            })
+        })
+        .collect();
+    sleep(Duration::from_millis(5)).await;
+    assert_eq!(guard.in_flight(), 4);
+
+    guard.close();
+    timeout(Duration::from_secs(1), guard.drain())
+        .await
+        .expect("drain() não resolveu");
+
+    assert_eq!(guard.in_flight(), 0);
+    for worker in workers {
+        assert!(worker.await.unwrap().is_ok());
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn drain_on_idle_guard_resolves_immediately() {
+    let guard = FlowGuard::new(FixedStrategy::new(2));
+
+    timeout(Duration::from_millis(50), guard.drain())
+        .await
+        .expect("drain() deveria resolver sem trabalho em andamento");
+}
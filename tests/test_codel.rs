@@ -0,0 +1,79 @@
+//! Gerenciamento da fila por CoDel: timeout curto enquanto a fila não esvazia.
+
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+const TARGET: Duration = Duration::from_millis(5);
+const INTERVAL: Duration = Duration::from_millis(50);
+
+/// Tempo que uma aquisição levou para desistir.
+async fn time_to_give_up(guard: &FlowGuard<FixedStrategy>) -> Duration {
+    let start = Instant::now();
+    assert!(matches!(
+        guard.acquire().await,
+        Err(FlowError::QueueTimeout)
+    ));
+    start.elapsed()
+}
+
+#[tokio::test(start_paused = true)]
+async fn standing_queue_switches_to_short_timeout() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_codel(TARGET, INTERVAL);
+    let holder = guard.acquire().await.unwrap();
+
+    // Sem sobrecarga detectada o timeout é o intervalo inteiro
+    assert!(!guard.is_overloaded());
+    assert!(time_to_give_up(&guard).await >= INTERVAL);
+
+    // Depois de um intervalo inteiro com sojourn acima do alvo, o timeout
+    // cai para o alvo
+    let mut waited = time_to_give_up(&guard).await;
+    for _ in 0..3 {
+        if waited < INTERVAL / 2 {
+            break;
+        }
+        waited = time_to_give_up(&guard).await;
+    }
+    assert!(guard.is_overloaded());
+    assert!(waited < INTERVAL / 2, "waited {waited:?}");
+    #[cfg(feature = "metrics-prometheus")]
+    assert!(guard.stats().timed_out >= 3);
+
+    drop(holder);
+}
+
+#[tokio::test(start_paused = true)]
+async fn overload_ends_once_the_queue_drains() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_codel(TARGET, INTERVAL);
+    let holder = guard.acquire().await.unwrap();
+    while !guard.is_overloaded() {
+        time_to_give_up(&guard).await;
+    }
+    holder.success();
+
+    // Aquisições sem espera contam como sojourn zero
+    for _ in 0..3 {
+        guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+        sleep(INTERVAL / 2).await;
+    }
+
+    assert!(!guard.is_overloaded());
+}
+
+#[tokio::test(start_paused = true)]
+async fn fixed_queue_timeout_still_caps_the_wait() {
+    let guard = FlowGuard::new(FixedStrategy::new(1))
+        .with_codel(TARGET, Duration::from_secs(10))
+        .with_queue_timeout(Duration::from_millis(20));
+    let _holder = guard.acquire().await.unwrap();
+
+    assert!(time_to_give_up(&guard).await < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn without_codel_nothing_is_overloaded() {
+    let guard = FlowGuard::new(FixedStrategy::new(1));
+
+    assert!(!guard.is_overloaded());
+}
@@ -0,0 +1,292 @@
+#![cfg(feature = "serde")]
+//! Configuração por arquivo com `FlowGuardConfig`/`StrategyConfig` (feature `serde`).
+
+use flow_guard::config::{AimdConfig, FixedConfig, LimitFnConfig, VegasConfig};
+use flow_guard::strategy::Aggregation;
+use flow_guard::{
+    AimdStrategy, ConfigError, Criticality, FlowError, FlowGuard, FlowGuardConfig,
+    GradientStrategy, LimitStrategy, QueueDiscipline, StrategyConfig, VegasStrategy,
+};
+use std::time::Duration;
+
+fn parse(text: &str) -> FlowGuardConfig {
+    toml::from_str(text).unwrap()
+}
+
+#[test]
+fn empty_config_uses_defaults() {
+    let config = parse("");
+    assert_eq!(config, FlowGuardConfig::default());
+    assert_eq!(
+        config.strategy,
+        StrategyConfig::Vegas(VegasConfig::default())
+    );
+
+    let guard = FlowGuard::from_config(&config).unwrap();
+    assert_eq!(guard.name(), "flow_guard");
+    assert_eq!(guard.current_limit(), 10);
+}
+
+#[test]
+fn every_strategy_parses_with_its_parameters() {
+    let vegas = parse(
+        r#"
+        [strategy]
+        type = "vegas"
+        initial_limit = 20
+        max_limit = 500
+        initial_base_rtt = "50ms"
+        alpha = 3.0
+        beta = 6.0
+        threshold = 1.0
+        base_rtt_window = 1000
+
+        [strategy.probe]
+        interval = 500
+        limit = 5
+        "#,
+    );
+    let StrategyConfig::Vegas(config) = &vegas.strategy else {
+        panic!("expected vegas, got {:?}", vegas.strategy);
+    };
+    assert_eq!(config.initial_base_rtt, Duration::from_millis(50));
+    assert_eq!(config.max_limit, Some(500));
+    let probe = config.probe.unwrap();
+    assert_eq!(
+        (probe.interval, probe.limit, probe.samples),
+        (500, Some(5), 10)
+    );
+
+    let aimd = parse(
+        r#"
+        [strategy]
+        type = "aimd"
+        initial_limit = 30
+        backoff_ratio = 0.5
+        timeout = "1.5s"
+        "#,
+    );
+    assert_eq!(
+        aimd.strategy,
+        StrategyConfig::Aimd(AimdConfig {
+            initial_limit: 30,
+            backoff_ratio: 0.5,
+            timeout: Duration::from_millis(1500),
+            ..AimdConfig::default()
+        })
+    );
+
+    let fixed = parse("strategy = { type = \"fixed\", limit = 7 }");
+    assert_eq!(
+        fixed.strategy,
+        StrategyConfig::Fixed(FixedConfig { limit: 7 })
+    );
+
+    for config in [&vegas, &aimd, &fixed] {
+        FlowGuard::from_config(config).unwrap();
+    }
+    assert_eq!(FlowGuard::from_config(&fixed).unwrap().current_limit(), 7);
+    assert_eq!(FlowGuard::from_config(&aimd).unwrap().current_limit(), 30);
+}
+
+#[test]
+fn windowed_wraps_another_strategy() {
+    let config = parse(
+        r#"
+        [strategy]
+        type = "windowed"
+        samples = 50
+        duration = "200ms"
+        aggregation = { percentile = 90.0 }
+        error_threshold = 0.25
+
+        [strategy.inner]
+        type = "gradient"
+        initial_limit = 40
+        tolerance = 2.0
+        "#,
+    );
+    let StrategyConfig::Windowed(windowed) = &config.strategy else {
+        panic!("expected windowed, got {:?}", config.strategy);
+    };
+    assert_eq!(windowed.aggregation, Aggregation::Percentile(90.0));
+    assert_eq!(windowed.duration, Some(Duration::from_millis(200)));
+    assert_eq!(windowed.error_threshold, 0.25);
+
+    let guard = FlowGuard::from_config(&config).unwrap();
+    assert_eq!(guard.current_limit(), 40);
+}
+
+#[tokio::test]
+async fn guard_options_are_applied() {
+    let config = parse(
+        r#"
+        name = "payments"
+        max_queue = 1
+        queue_timeout = "20ms"
+        queue_discipline = { adaptive_lifo = { threshold = "5ms" } }
+        codel = { target = "5ms", interval = "100ms" }
+
+        [criticality_thresholds]
+        sheddable = 0.5
+
+        [strategy]
+        type = "fixed"
+        limit = 2
+        "#,
+    );
+    assert_eq!(
+        config.queue_discipline,
+        QueueDiscipline::AdaptiveLifo {
+            threshold: Duration::from_millis(5)
+        }
+    );
+    assert_eq!(config.criticality_thresholds[&Criticality::Sheddable], 0.5);
+
+    let guard = FlowGuard::from_config(&config).unwrap();
+    assert_eq!(guard.name(), "payments");
+
+    // Limiar de 0.5 com limite 2: Sheddable só entra com o guard ocioso
+    let _first = guard.try_acquire().unwrap();
+    let shed = guard
+        .acquire_with_criticality(Criticality::Sheddable)
+        .await
+        .err();
+    assert!(matches!(shed, Some(FlowError::Dropped)));
+}
+
+#[test]
+fn vegas_thresholds_can_depend_on_the_limit() {
+    let config = parse(
+        r#"
+        [strategy]
+        type = "vegas"
+        initial_limit = 100
+        max_limit = 1000
+        alpha = { log10 = 3.0 }
+        beta = { log10 = 6.0 }
+        threshold = { log10 = 1.0 }
+        "#,
+    );
+    let StrategyConfig::Vegas(vegas) = &config.strategy else {
+        panic!("expected vegas, got {:?}", config.strategy);
+    };
+    assert_eq!(vegas.alpha, LimitFnConfig::Log10 { log10: 3.0 });
+    assert_eq!(vegas.threshold, Some(LimitFnConfig::Log10 { log10: 1.0 }));
+
+    // Com limite 100 o threshold vale 2: RTT igual ao base é fila vazia e o
+    // limite cresce beta(100) = 12 de uma vez
+    let strategy = vegas.build().unwrap();
+    strategy.on_success(Duration::from_millis(500));
+    assert_eq!(strategy.current_limit(), 112);
+
+    let text = toml::to_string(&config).unwrap();
+    assert_eq!(parse(&text), config);
+
+    // Números continuam valendo como constantes
+    let constant = parse("strategy = { type = \"vegas\", alpha = 3.0, beta = 6.0 }");
+    let StrategyConfig::Vegas(vegas) = &constant.strategy else {
+        panic!("expected vegas, got {:?}", constant.strategy);
+    };
+    assert_eq!(vegas.alpha, LimitFnConfig::Constant(3.0));
+
+    let unknown = toml::from_str::<FlowGuardConfig>(
+        "strategy = { type = \"vegas\", alpha = { log2 = 3.0 } }",
+    );
+    assert!(unknown.is_err());
+}
+
+#[test]
+fn round_trips_through_toml() {
+    let config = parse(
+        r#"
+        name = "search"
+        queue_timeout = "250us"
+
+        [strategy]
+        type = "aimd"
+        timeout = "2s"
+        "#,
+    );
+
+    let text = toml::to_string(&config).unwrap();
+    assert!(text.contains("queue_timeout = \"250us\""), "{text}");
+    assert!(text.contains("timeout = \"2s\""), "{text}");
+    assert_eq!(parse(&text), config);
+}
+
+#[test]
+fn invalid_parameters_are_errors_not_panics() {
+    let build = |text: &str| FlowGuard::from_config(&parse(text)).err();
+
+    assert_eq!(
+        build("strategy = { type = \"aimd\", backoff_ratio = 1.5 }"),
+        Some(ConfigError::InvalidParameter {
+            name: "backoff_ratio",
+            reason: "must be between 0.0 and 1.0 (exclusive)"
+        })
+    );
+    assert_eq!(
+        build("strategy = { type = \"gradient\", min_limit = 0 }"),
+        Some(ConfigError::ZeroMinLimit)
+    );
+    assert!(matches!(
+        build("strategy = { type = \"vegas\", alpha = 5.0, beta = 1.0 }"),
+        Some(ConfigError::AlphaAboveBeta { .. })
+    ));
+    assert!(build("strategy = { type = \"fixed\", limit = 0 }").is_some());
+    assert!(build("criticality_thresholds = { high = 0.0 }").is_some());
+
+    assert_eq!(
+        build("codel = { target = \"5ms\", interval = \"0ms\" }"),
+        Some(ConfigError::InvalidParameter {
+            name: "codel.interval",
+            reason: "must be greater than zero"
+        })
+    );
+    assert_eq!(
+        build("codel = { target = \"0ms\", interval = \"100ms\" }"),
+        Some(ConfigError::InvalidParameter {
+            name: "codel.target",
+            reason: "must be greater than zero"
+        })
+    );
+    assert_eq!(
+        build("codel = { target = \"200ms\", interval = \"100ms\" }"),
+        Some(ConfigError::InvalidParameter {
+            name: "codel.target",
+            reason: "must not exceed codel.interval"
+        })
+    );
+    assert!(build("codel = { target = \"5ms\", interval = \"100ms\" }").is_none());
+}
+
+#[test]
+fn huge_initial_limit_does_not_overflow_the_default_max() {
+    for kind in ["vegas", "aimd", "gradient"] {
+        let config = parse(&format!(
+            "strategy = {{ type = \"{kind}\", initial_limit = {} }}",
+            i64::MAX
+        ));
+        let guard = FlowGuard::from_config(&config).unwrap();
+        assert_eq!(guard.current_limit(), i64::MAX as usize, "{kind}");
+    }
+
+    let _ = AimdStrategy::new(usize::MAX);
+    let _ = GradientStrategy::new(usize::MAX);
+    let _ = VegasStrategy::new(usize::MAX);
+}
+
+#[test]
+fn malformed_files_are_rejected() {
+    let unknown_strategy = toml::from_str::<FlowGuardConfig>("strategy = { type = \"bbr\" }");
+    assert!(unknown_strategy.is_err());
+
+    let typo = toml::from_str::<FlowGuardConfig>("strategy = { type = \"vegas\", alhpa = 3.0 }");
+    assert!(typo.is_err());
+
+    let error = toml::from_str::<FlowGuardConfig>("queue_timeout = \"10\"")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("missing unit"), "{error}");
+}
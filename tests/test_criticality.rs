@@ -0,0 +1,189 @@
+//! Níveis de criticidade: fila ordenada por prioridade e limiares de
+//! utilização.
+
+use flow_guard::{Criticality, FixedStrategy, FlowError, FlowGuard};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{sleep, timeout};
+
+type Order = Arc<Mutex<Vec<&'static str>>>;
+
+/// Enfileira uma execução que registra `name` ao ser admitida.
+async fn enqueue(
+    guard: &Arc<FlowGuard<FixedStrategy>>,
+    order: &Order,
+    criticality: Criticality,
+    name: &'static str,
+) -> tokio::task::JoinHandle<()> {
+    let guard = Arc::clone(guard);
+    let order = Arc::clone(order);
+    let handle = tokio::spawn(async move {
+        guard
+            .run_with_criticality(criticality, async {
+                order.lock().push(name);
+                Ok::<_, &str>(())
+            })
+            .await
+            .unwrap();
+    });
+    // Garante a ordem de chegada na fila
+    sleep(Duration::from_millis(5)).await;
+    handle
+}
+
+#[tokio::test(start_paused = true)]
+async fn queue_is_ordered_by_criticality() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)));
+    let order = Order::default();
+    let holder = guard.acquire().await.unwrap();
+
+    let handles = vec![
+        enqueue(&guard, &order, Criticality::Normal, "normal").await,
+        enqueue(&guard, &order, Criticality::High, "high").await,
+        enqueue(&guard, &order, Criticality::Critical, "critical").await,
+    ];
+    assert_eq!(guard.queue_len(), 3);
+
+    holder.success();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(*order.lock(), ["critical", "high", "normal"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn same_level_is_served_in_arrival_order() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)));
+    let order = Order::default();
+    let holder = guard.acquire().await.unwrap();
+
+    let handles = vec![
+        enqueue(&guard, &order, Criticality::High, "first").await,
+        enqueue(&guard, &order, Criticality::High, "second").await,
+        enqueue(&guard, &order, Criticality::High, "third").await,
+    ];
+
+    holder.success();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(*order.lock(), ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn sheddable_is_rejected_above_default_threshold() {
+    let guard = FlowGuard::new(FixedStrategy::new(10));
+    let mut held = Vec::new();
+    for _ in 0..8 {
+        held.push(guard.acquire().await.unwrap());
+    }
+
+    // 80% do limite em uso: `Sheddable` é rejeitado sem entrar na fila
+    assert!(matches!(
+        guard
+            .run_with_criticality(Criticality::Sheddable, async { Ok::<_, &str>(()) })
+            .await,
+        Err(FlowError::Dropped)
+    ));
+    assert!(guard
+        .run_with_criticality(Criticality::Normal, async { Ok::<_, &str>(()) })
+        .await
+        .is_ok());
+    #[cfg(feature = "metrics-prometheus")]
+    assert_eq!(guard.stats().dropped, 1);
+}
+
+#[tokio::test]
+async fn thresholds_are_configurable() {
+    let guard = FlowGuard::new(FixedStrategy::new(10))
+        .with_criticality_threshold(Criticality::Normal, 0.5)
+        .with_criticality_threshold(Criticality::Sheddable, 0.2);
+    let mut held = Vec::new();
+    for _ in 0..2 {
+        held.push(
+            guard
+                .acquire_with_criticality(Criticality::High)
+                .await
+                .unwrap(),
+        );
+    }
+
+    assert!(matches!(
+        guard.acquire_with_criticality(Criticality::Sheddable).await,
+        Err(FlowError::Dropped)
+    ));
+    for _ in 0..3 {
+        held.push(guard.acquire().await.unwrap());
+    }
+    assert!(matches!(guard.acquire().await, Err(FlowError::Dropped)));
+    assert!(guard
+        .acquire_with_criticality(Criticality::High)
+        .await
+        .is_ok());
+}
+
+#[test]
+#[should_panic(expected = "criticality threshold")]
+fn rejects_invalid_threshold() {
+    let _ =
+        FlowGuard::new(FixedStrategy::new(10)).with_criticality_threshold(Criticality::Normal, 1.5);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelled_waiter_does_not_keep_its_place() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)));
+    let holder = guard.acquire().await.unwrap();
+
+    let cancelled = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move {
+            guard
+                .acquire_with_criticality(Criticality::Critical)
+                .await
+                .map(|token| token.success())
+        })
+    };
+    sleep(Duration::from_millis(5)).await;
+    let (tx, rx) = oneshot::channel();
+    let waiter = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move {
+            let token = guard.acquire().await.unwrap();
+            tx.send(()).unwrap();
+            token.success();
+        })
+    };
+    sleep(Duration::from_millis(5)).await;
+    assert_eq!(guard.queue_len(), 2);
+
+    cancelled.abort();
+    let _ = cancelled.await;
+    assert_eq!(guard.queue_len(), 1);
+
+    holder.success();
+    timeout(Duration::from_secs(1), rx)
+        .await
+        .expect("the remaining waiter should get the permit")
+        .unwrap();
+    waiter.await.unwrap();
+    assert_eq!(guard.in_flight(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn timed_out_waiter_leaves_the_queue() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_queue_timeout(Duration::from_millis(10));
+    let holder = guard.acquire().await.unwrap();
+
+    assert!(matches!(
+        guard.acquire_with_criticality(Criticality::Critical).await,
+        Err(FlowError::QueueTimeout)
+    ));
+    assert_eq!(guard.queue_len(), 0);
+
+    holder.success();
+    assert!(guard.try_acquire().is_ok());
+}
@@ -0,0 +1,103 @@
+//! API RAII: `FlowGuard::acquire()` / `try_acquire()` e `FlowToken`.
+
+mod common;
+
+use common::Recorder;
+use flow_guard::{FlowError, FlowGuard, Outcome};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test(start_paused = true)]
+async fn success_reports_latency_and_releases_permit() {
+    let strategy = Arc::new(Recorder::new(2));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    let token = guard.acquire().await.unwrap();
+    assert_eq!(guard.in_flight(), 1);
+    sleep(Duration::from_millis(20)).await;
+    token.success();
+
+    assert_eq!(guard.in_flight(), 0);
+    let latencies = strategy.latencies();
+    assert_eq!(latencies.len(), 1);
+    assert!(latencies[0] >= Duration::from_millis(20));
+}
+
+#[tokio::test]
+async fn dropped_and_ignore_report_accordingly() {
+    let strategy = Arc::new(Recorder::new(2));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    guard.acquire().await.unwrap().dropped();
+    guard.acquire().await.unwrap().ignore();
+
+    assert_eq!(strategy.errors(), 1);
+    assert!(strategy.latencies().is_empty());
+    assert_eq!(guard.available_permits(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unreported_token_uses_default_outcome() {
+    let strategy = Arc::new(Recorder::new(2));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    // Padrão: sucesso, com a latência chegando à estratégia
+    {
+        let _token = guard.acquire().await.unwrap();
+        sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(strategy.latencies().len(), 1);
+    assert!(strategy.latencies()[0] >= Duration::from_millis(10));
+
+    // Configurado: descarte sem relatório conta como sobrecarga
+    let guard = guard.with_default_outcome(Outcome::Dropped);
+    drop(guard.acquire().await.unwrap());
+    assert_eq!(strategy.errors(), 1);
+    assert_eq!(guard.available_permits(), 2);
+}
+
+#[tokio::test]
+async fn try_acquire_drops_when_saturated() {
+    let guard = FlowGuard::new(Arc::new(Recorder::new(2)));
+
+    let first = guard.try_acquire().unwrap();
+    let second = guard.try_acquire().unwrap();
+    assert!(matches!(guard.try_acquire(), Err(FlowError::Dropped)));
+
+    first.success();
+    let third = guard.try_acquire().unwrap();
+    second.ignore();
+    third.ignore();
+    assert_eq!(guard.available_permits(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn token_can_be_moved_into_another_task() {
+    let strategy = Arc::new(Recorder::new(2));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    let token = guard.acquire().await.unwrap();
+    tokio::spawn(async move {
+        sleep(Duration::from_millis(5)).await;
+        token.success();
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(guard.in_flight(), 0);
+    assert_eq!(strategy.latencies().len(), 1);
+}
+
+#[test]
+fn token_works_from_sync_code() {
+    let strategy = Arc::new(Recorder::new(2));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    let token = guard.try_acquire().unwrap();
+    std::thread::sleep(Duration::from_millis(5));
+    assert!(token.elapsed() >= Duration::from_millis(5));
+    token.success();
+
+    assert_eq!(strategy.latencies().len(), 1);
+}
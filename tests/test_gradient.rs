@@ -0,0 +1,152 @@
+//! `GradientStrategy`: limite guiado pela razão entre RTT longo e curto.
+
+use flow_guard::{FlowGuard, GradientStrategy, LimitStrategy};
+use std::time::Duration;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn stable_latency_grows_limit_by_queue_allowance() {
+    let strategy = GradientStrategy::new(20).with_max_limit(1000);
+
+    for _ in 0..200 {
+        strategy.on_success(ms(10));
+    }
+
+    assert!(strategy.current_limit() > 100);
+    assert!((strategy.long_rtt().as_secs_f64() - 0.010).abs() < 1e-6);
+}
+
+#[test]
+fn tolerates_moderate_latency_variance() {
+    let strategy = GradientStrategy::new(50);
+
+    for _ in 0..50 {
+        strategy.on_success(ms(10));
+    }
+    let before = strategy.current_limit();
+
+    // Picos até 1.5x o RTT de longo prazo ainda não derrubam o limite
+    for i in 0..100 {
+        strategy.on_success(if i % 2 == 0 { ms(8) } else { ms(14) });
+    }
+
+    assert!(strategy.current_limit() >= before);
+}
+
+#[test]
+fn sustained_latency_increase_shrinks_limit() {
+    let strategy = GradientStrategy::new(100)
+        .with_max_limit(100)
+        .with_long_window(1000);
+
+    for _ in 0..100 {
+        strategy.on_success(ms(10));
+    }
+    assert_eq!(strategy.current_limit(), 100);
+
+    for _ in 0..30 {
+        strategy.on_success(ms(100));
+    }
+
+    assert!(strategy.current_limit() < 60);
+}
+
+#[test]
+fn errors_apply_minimum_gradient() {
+    let strategy = GradientStrategy::new(100)
+        .with_queue_size(0)
+        .with_smoothing(1.0);
+
+    strategy.on_error();
+
+    assert_eq!(strategy.current_limit(), 50);
+}
+
+#[test]
+fn respects_min_and_max_limits() {
+    let strategy = GradientStrategy::new(10)
+        .with_min_limit(5)
+        .with_max_limit(12);
+
+    for _ in 0..100 {
+        strategy.on_success(ms(10));
+    }
+    assert_eq!(strategy.current_limit(), 12);
+
+    for _ in 0..100 {
+        strategy.on_error();
+    }
+    assert_eq!(strategy.current_limit(), 5);
+}
+
+#[test]
+#[should_panic(expected = "tolerance")]
+fn rejects_tolerance_below_one() {
+    let _ = GradientStrategy::new(10).with_tolerance(0.5);
+}
+
+#[test]
+fn light_load_does_not_grow_limit() {
+    let strategy = GradientStrategy::new(20).with_max_limit(1000);
+
+    // Só 3 execuções em andamento: bem abaixo da metade do limite
+    for _ in 0..200 {
+        strategy.on_success_with_in_flight(ms(10), 3);
+    }
+    assert_eq!(strategy.current_limit(), 20);
+    assert!((strategy.long_rtt().as_secs_f64() - 0.010).abs() < 1e-6);
+
+    // Com o limite em uso volta a crescer
+    for _ in 0..50 {
+        strategy.on_success_with_in_flight(ms(10), 20);
+    }
+    assert!(strategy.current_limit() > 20);
+}
+
+#[tokio::test]
+async fn sequential_guard_traffic_keeps_limit() {
+    let guard = FlowGuard::new(GradientStrategy::new(20).with_max_limit(1000));
+
+    for _ in 0..100 {
+        guard.run(async { Ok::<_, ()>(()) }).await.unwrap();
+    }
+
+    assert_eq!(guard.current_limit(), 20);
+}
+
+#[test]
+fn initial_limit_is_clamped_to_bounds() {
+    let strategy = GradientStrategy::new(100).with_max_limit(10);
+    assert_eq!(strategy.current_limit(), 10);
+
+    let strategy = GradientStrategy::new(2).with_min_limit(5);
+    assert_eq!(strategy.current_limit(), 5);
+}
+
+#[test]
+fn crossed_bounds_do_not_panic() {
+    let strategy = GradientStrategy::new(30)
+        .with_min_limit(50)
+        .with_max_limit(20);
+
+    strategy.on_error();
+    strategy.on_success(ms(10));
+
+    // O mínimo prevalece
+    assert_eq!(strategy.current_limit(), 50);
+}
+
+#[test]
+#[should_panic(expected = "min_limit")]
+fn rejects_zero_min_limit() {
+    let _ = GradientStrategy::new(10).with_min_limit(0);
+}
+
+#[test]
+#[should_panic(expected = "initial_limit")]
+fn rejects_zero_initial_limit() {
+    let _ = GradientStrategy::new(0);
+}
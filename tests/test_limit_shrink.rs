@@ -0,0 +1,185 @@
+//! Redução do limite do semáforo: a dívida precisa ser paga antes de novas
+//! aquisições, em vez de esperar que as permissões extras "sumam sozinhas".
+
+mod common;
+
+use common::ManualLimit;
+use flow_guard::FlowGuard;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+/// Dispara `count` execuções que só terminam quando o sender correspondente é usado.
+fn spawn_holders(
+    guard: &Arc<FlowGuard<Arc<ManualLimit>>>,
+    count: usize,
+) -> (Vec<oneshot::Sender<()>>, Vec<tokio::task::JoinHandle<()>>) {
+    let mut senders = Vec::new();
+    let mut handles = Vec::new();
+    for _ in 0..count {
+        let (tx, rx) = oneshot::channel::<()>();
+        let guard = Arc::clone(guard);
+        senders.push(tx);
+        handles.push(tokio::spawn(async move {
+            guard
+                .run(async move {
+                    let _ = rx.await;
+                    Ok::<_, &str>(())
+                })
+                .await
+                .unwrap();
+        }));
+    }
+    (senders, handles)
+}
+
+#[tokio::test(start_paused = true)]
+async fn shrink_blocks_new_acquires_until_debt_is_paid() {
+    let strategy = Arc::new(ManualLimit::new(10));
+    let guard = Arc::new(FlowGuard::new(Arc::clone(&strategy)));
+
+    let (mut senders, mut handles) = spawn_holders(&guard, 10);
+    sleep(Duration::from_millis(20)).await;
+    assert_eq!(guard.in_flight(), 10);
+    assert_eq!(guard.available_permits(), 0);
+
+    // A próxima execução a terminar aplica o novo limite (4) ao semáforo
+    strategy.set(4);
+    senders.remove(0).send(()).unwrap();
+    handles.remove(0).await.unwrap();
+    assert_eq!(guard.in_flight(), 9);
+    assert_eq!(guard.available_permits(), 0);
+
+    // Novas execuções ficam esperando enquanto houver dívida
+    let admitted = Arc::new(AtomicUsize::new(0));
+    let violations = Arc::new(AtomicUsize::new(0));
+    let newcomers: Vec<_> = (0..20)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            let admitted = Arc::clone(&admitted);
+            let violations = Arc::clone(&violations);
+            tokio::spawn(async move {
+                guard
+                    .run(async {
+                        admitted.fetch_add(1, Ordering::SeqCst);
+                        if guard.in_flight() > guard.current_limit() {
+                            violations.fetch_add(1, Ordering::SeqCst);
+                        }
+                        sleep(Duration::from_millis(5)).await;
+                        Ok::<_, &str>(())
+                    })
+                    .await
+                    .unwrap();
+            })
+        })
+        .collect();
+
+    // Libera 5 das 9 restantes: in-flight vai a 4, ainda sem vaga
+    for _ in 0..5 {
+        senders.remove(0).send(()).unwrap();
+        handles.remove(0).await.unwrap();
+    }
+    sleep(Duration::from_millis(20)).await;
+    assert_eq!(admitted.load(Ordering::SeqCst), 0);
+    assert_eq!(guard.in_flight(), 4);
+    assert_eq!(guard.available_permits(), 0);
+
+    // Libera o resto: os recém-chegados entram, sempre dentro do limite
+    for (tx, handle) in senders.into_iter().zip(handles) {
+        tx.send(()).unwrap();
+        handle.await.unwrap();
+    }
+    for newcomer in newcomers {
+        newcomer.await.unwrap();
+    }
+
+    assert_eq!(admitted.load(Ordering::SeqCst), 20);
+    assert_eq!(violations.load(Ordering::SeqCst), 0);
+    assert_eq!(guard.in_flight(), 0);
+    assert_eq!(guard.available_permits(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn in_flight_never_exceeds_limit_after_repeated_shrinks() {
+    let strategy = Arc::new(ManualLimit::new(16));
+    let guard = Arc::new(FlowGuard::new(Arc::clone(&strategy)));
+    let peak_after_shrink = Arc::new(AtomicUsize::new(0));
+
+    // Aquece com o limite cheio e depois reduz para 3
+    let (senders, handles) = spawn_holders(&guard, 16);
+    while guard.in_flight() < 16 {
+        tokio::task::yield_now().await;
+    }
+    strategy.set(3);
+    for (tx, handle) in senders.into_iter().zip(handles) {
+        tx.send(()).unwrap();
+        handle.await.unwrap();
+    }
+    assert_eq!(guard.available_permits(), 3);
+
+    let tasks: Vec<_> = (0..500)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            let peak = Arc::clone(&peak_after_shrink);
+            tokio::spawn(async move {
+                guard
+                    .run(async {
+                        peak.fetch_max(guard.in_flight(), Ordering::SeqCst);
+                        assert!(guard.available_permits() <= guard.current_limit());
+                        tokio::task::yield_now().await;
+                        Ok::<_, &str>(())
+                    })
+                    .await
+                    .unwrap();
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert!(peak_after_shrink.load(Ordering::SeqCst) <= 3);
+    assert_eq!(guard.in_flight(), 0);
+    assert_eq!(guard.available_permits(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn growth_after_shrink_wakes_waiters() {
+    let strategy = Arc::new(ManualLimit::new(2));
+    let guard = Arc::new(FlowGuard::new(Arc::clone(&strategy)));
+
+    let (senders, handles) = spawn_holders(&guard, 2);
+    sleep(Duration::from_millis(10)).await;
+
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move {
+                guard
+                    .run(async {
+                        sleep(Duration::from_millis(20)).await;
+                        Ok::<_, &str>(())
+                    })
+                    .await
+                    .unwrap();
+            })
+        })
+        .collect();
+
+    // Cresce para 6: ao terminar, o holder aplica o limite e as 4 esperas entram
+    strategy.set(6);
+    for (tx, handle) in senders.into_iter().zip(handles) {
+        tx.send(()).unwrap();
+        handle.await.unwrap();
+    }
+    sleep(Duration::from_millis(5)).await;
+    assert_eq!(guard.in_flight(), 4);
+
+    for waiter in waiters {
+        waiter.await.unwrap();
+    }
+    assert_eq!(guard.available_permits(), 6);
+}
@@ -0,0 +1,143 @@
+//! Emissão de métricas pela fachada do crate `metrics` (feature `metrics`).
+#![cfg(feature = "metrics")]
+
+use flow_guard::metrics::MetricsConfig;
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use metrics::{SharedString, Unit};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use metrics_util::CompositeKey;
+
+type Metrics = Vec<(CompositeKey, Option<Unit>, Option<SharedString>, DebugValue)>;
+
+/// Fotografia do recorder (o `DebuggingRecorder` zera os valores a cada leitura).
+fn snapshot(snapshotter: &Snapshotter) -> Metrics {
+    snapshotter.snapshot().into_vec()
+}
+
+/// Valor da métrica `name` cujos rótulos incluem `label`.
+fn value<'a>(metrics: &'a Metrics, name: &str, label: (&str, &str)) -> Option<&'a DebugValue> {
+    metrics
+        .iter()
+        .find(|(key, _, _, _)| {
+            key.key().name() == name
+                && key
+                    .key()
+                    .labels()
+                    .any(|l| l.key() == label.0 && l.value() == label.1)
+        })
+        .map(|(_, _, _, value)| value)
+}
+
+fn gauge(value: Option<&DebugValue>) -> f64 {
+    match value {
+        Some(DebugValue::Gauge(v)) => v.into_inner(),
+        other => panic!("expected gauge, got {other:?}"),
+    }
+}
+
+fn counter(value: Option<&DebugValue>) -> u64 {
+    match value {
+        Some(DebugValue::Counter(v)) => *v,
+        other => panic!("expected counter, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn emits_gauges_counters_and_histogram() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let guard = metrics::with_local_recorder(&recorder, || {
+        FlowGuard::new(FixedStrategy::new(1))
+            .with_metrics(MetricsConfig::new().with_label("guard", "db"))
+    });
+    let db = ("guard", "db");
+
+    let token = guard.acquire().await.unwrap();
+    assert!(matches!(
+        guard.try_run(async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::Dropped)
+    ));
+    token.success();
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    guard.close();
+    let _ = guard.run(async { Ok::<_, &str>(()) }).await;
+
+    let metrics = snapshot(&snapshotter);
+    assert_eq!(gauge(value(&metrics, "flowguard_limit", db)), 1.0);
+    assert_eq!(gauge(value(&metrics, "flowguard_in_flight", db)), 0.0);
+    assert_eq!(gauge(value(&metrics, "flowguard_queue_depth", db)), 0.0);
+    assert_eq!(counter(value(&metrics, "flowguard_acquired_total", db)), 2);
+    assert_eq!(counter(value(&metrics, "flowguard_dropped_total", db)), 1);
+    assert_eq!(counter(value(&metrics, "flowguard_rejected_total", db)), 1);
+    assert_eq!(counter(value(&metrics, "flowguard_timed_out_total", db)), 0);
+    match value(&metrics, "flowguard_latency_seconds", db) {
+        Some(DebugValue::Histogram(samples)) => assert_eq!(samples.len(), 2),
+        other => panic!("expected histogram, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn in_flight_gauge_follows_held_permits() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let guard = metrics::with_local_recorder(&recorder, || {
+        FlowGuard::new(FixedStrategy::new(4))
+            .with_metrics(MetricsConfig::new().with_label("guard", "db"))
+    });
+    let db = ("guard", "db");
+
+    let first = guard.acquire().await.unwrap();
+    let _second = guard.acquire().await.unwrap();
+    let metrics = snapshot(&snapshotter);
+    assert_eq!(gauge(value(&metrics, "flowguard_in_flight", db)), 2.0);
+
+    first.success();
+    let metrics = snapshot(&snapshotter);
+    assert_eq!(gauge(value(&metrics, "flowguard_in_flight", db)), 1.0);
+}
+
+#[tokio::test]
+async fn guards_are_told_apart_by_prefix_and_labels() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let (db, cache) = metrics::with_local_recorder(&recorder, || {
+        (
+            FlowGuard::new(FixedStrategy::new(10))
+                .with_metrics(MetricsConfig::new().with_label("guard", "db")),
+            FlowGuard::new(FixedStrategy::new(50)).with_metrics(
+                MetricsConfig::with_prefix("cache_guard").with_label("region", "us-east"),
+            ),
+        )
+    });
+
+    db.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    cache.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    cache.run(async { Ok::<_, &str>(()) }).await.unwrap();
+
+    let metrics = snapshot(&snapshotter);
+    let region = ("region", "us-east");
+    assert_eq!(
+        gauge(value(&metrics, "flowguard_limit", ("guard", "db"))),
+        10.0
+    );
+    assert_eq!(gauge(value(&metrics, "cache_guard_limit", region)), 50.0);
+    assert_eq!(
+        counter(value(&metrics, "flowguard_acquired_total", ("guard", "db"))),
+        1
+    );
+    assert_eq!(
+        counter(value(&metrics, "cache_guard_acquired_total", region)),
+        2
+    );
+}
+
+#[tokio::test]
+async fn guard_without_metrics_emits_nothing() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let guard = metrics::with_local_recorder(&recorder, || FlowGuard::new(FixedStrategy::new(1)));
+
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+
+    assert!(snapshotter.snapshot().into_vec().is_empty());
+}
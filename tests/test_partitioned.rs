@@ -0,0 +1,369 @@
+//! Limite particionado por chave: cotas, empréstimo de folga e layer tower.
+
+mod common;
+
+use common::ManualLimit;
+use flow_guard::partitioned::PartitionedFlowGuard;
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+
+type Guard = Arc<PartitionedFlowGuard<&'static str, ManualLimit>>;
+
+fn tenants(limit: usize) -> Guard {
+    Arc::new(
+        PartitionedFlowGuard::new(ManualLimit::new(limit))
+            .with_partition("noisy", 1.0)
+            .with_partition("quiet", 1.0),
+    )
+}
+
+/// Ocupa uma permissão de `key` até o sender ser usado (ou descartado).
+async fn hold(guard: &Guard, key: &'static str) -> (oneshot::Sender<()>, JoinHandle<()>) {
+    let (tx, rx) = oneshot::channel::<()>();
+    let guard = Arc::clone(guard);
+    let handle = tokio::spawn(async move {
+        guard
+            .run(key, async move {
+                let _ = rx.await;
+                Ok::<_, &str>(())
+            })
+            .await
+            .unwrap();
+    });
+    sleep(Duration::from_millis(5)).await;
+    (tx, handle)
+}
+
+async fn shed(guard: &Guard, key: &'static str) -> bool {
+    matches!(
+        guard.try_run(key, async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::Dropped)
+    )
+}
+
+#[test]
+fn shares_follow_weights() {
+    let guard = PartitionedFlowGuard::new(FixedStrategy::new(10))
+        .with_partition("a", 3.0)
+        .with_partition("b", 1.0)
+        .with_default_weight(1.0);
+
+    assert_eq!(guard.share("a"), 6);
+    assert_eq!(guard.share("b"), 2);
+    assert_eq!(guard.share("unknown"), 2);
+}
+
+#[test]
+fn unregistered_keys_only_borrow_by_default() {
+    let guard = PartitionedFlowGuard::new(FixedStrategy::new(10)).with_partition("a", 1.0);
+
+    assert_eq!(guard.share("a"), 10);
+    assert_eq!(guard.share("other"), 0);
+}
+
+#[test]
+fn remainder_goes_to_the_heaviest_partitions() {
+    let guard = PartitionedFlowGuard::new(FixedStrategy::new(10))
+        .with_partition("a", 1.0)
+        .with_partition("b", 2.0)
+        .with_partition("c", 1.0);
+
+    // Pisos 2, 5 e 2; a vaga que sobra vai para o maior peso
+    assert_eq!(guard.share("b"), 6);
+    assert_eq!(guard.share("a"), 2);
+    assert_eq!(guard.share("c"), 2);
+}
+
+#[test]
+fn shares_never_add_up_to_more_than_the_limit() {
+    let mut guard = PartitionedFlowGuard::new(FixedStrategy::new(4));
+    for key in 0..16 {
+        guard = guard.with_partition(key, 1.0);
+    }
+
+    // Empate: as primeiras registradas ficam com as 4 vagas
+    let shares: Vec<usize> = (0..16).map(|key| guard.share(&key)).collect();
+    assert_eq!(shares.iter().sum::<usize>(), 4);
+    assert_eq!(shares[..4], [1, 1, 1, 1]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn many_partitions_stay_within_the_global_limit() {
+    let mut guard = PartitionedFlowGuard::new(FixedStrategy::new(4));
+    for key in 0..16 {
+        guard = guard.with_partition(key, 1.0);
+    }
+    let guard = Arc::new(guard);
+
+    let mut held = Vec::new();
+    for key in 0..16 {
+        let (tx, rx) = oneshot::channel::<()>();
+        let task_guard = Arc::clone(&guard);
+        held.push((
+            tx,
+            tokio::spawn(async move {
+                task_guard
+                    .try_run(&key, async move {
+                        let _ = rx.await;
+                        Ok::<_, &str>(())
+                    })
+                    .await
+            }),
+        ));
+        sleep(Duration::from_millis(1)).await;
+        assert!(guard.in_flight() <= guard.current_limit());
+    }
+    assert_eq!(guard.in_flight(), 4);
+
+    let mut admitted = 0;
+    for (tx, handle) in held {
+        let _ = tx.send(());
+        if handle.await.unwrap().is_ok() {
+            admitted += 1;
+        }
+    }
+    assert_eq!(admitted, 4);
+}
+
+#[test]
+#[should_panic(expected = "partition weight")]
+fn rejects_negative_weight() {
+    let _ = PartitionedFlowGuard::new(FixedStrategy::new(10)).with_partition("a", -1.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn idle_capacity_can_be_borrowed() {
+    let guard = tenants(4);
+
+    // A cota de "noisy" é 2, mas com "quiet" ocioso ele usa o limite inteiro
+    let mut held = Vec::new();
+    for _ in 0..4 {
+        held.push(hold(&guard, "noisy").await);
+    }
+    assert_eq!(guard.partition_in_flight("noisy"), 4);
+    assert!(shed(&guard, "noisy").await);
+
+    for (tx, handle) in held {
+        tx.send(()).unwrap();
+        handle.await.unwrap();
+    }
+    assert_eq!(guard.in_flight(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn share_is_guaranteed_even_after_others_borrow() {
+    let guard = tenants(4);
+
+    let mut held = Vec::new();
+    for _ in 0..4 {
+        held.push(hold(&guard, "noisy").await);
+    }
+
+    // Limite global esgotado, mas "quiet" está abaixo da sua cota de 2
+    held.push(hold(&guard, "quiet").await);
+    held.push(hold(&guard, "quiet").await);
+    assert_eq!(guard.partition_in_flight("quiet"), 2);
+    assert_eq!(guard.in_flight(), 6);
+
+    // Acima da cota e sem folga: descarta
+    assert!(shed(&guard, "quiet").await);
+    assert!(shed(&guard, "noisy").await);
+    assert!(shed(&guard, "unknown").await);
+
+    for (tx, handle) in held {
+        tx.send(()).unwrap();
+        handle.await.unwrap();
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiters_are_admitted_when_permits_are_released() {
+    let guard = tenants(2);
+    let (first, first_handle) = hold(&guard, "noisy").await;
+    let (second, second_handle) = hold(&guard, "noisy").await;
+
+    let waiter = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move { guard.run("noisy", async { Ok::<_, &str>(()) }).await })
+    };
+    sleep(Duration::from_millis(10)).await;
+    assert!(!waiter.is_finished());
+
+    first.send(()).unwrap();
+    timeout(Duration::from_secs(1), waiter)
+        .await
+        .expect("waiter should be admitted")
+        .unwrap()
+        .unwrap();
+
+    second.send(()).unwrap();
+    first_handle.await.unwrap();
+    second_handle.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn strategy_still_decides_the_global_limit() {
+    let guard = tenants(4);
+
+    let _ = guard
+        .run("noisy", async { Err::<(), _>("overloaded") })
+        .await;
+
+    assert_eq!(guard.current_limit(), 3);
+    assert_eq!(guard.share("quiet"), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn partition_waiters_are_served_in_arrival_order() {
+    let guard = tenants(2);
+    let (noisy, noisy_handle) = hold(&guard, "noisy").await;
+    let (quiet, quiet_handle) = hold(&guard, "quiet").await;
+
+    let order = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let mut waiters = Vec::new();
+    for i in 0..3 {
+        let guard = Arc::clone(&guard);
+        let order = Arc::clone(&order);
+        waiters.push(tokio::spawn(async move {
+            guard
+                .run("noisy", async move {
+                    order.lock().push(i);
+                    Ok::<_, &str>(())
+                })
+                .await
+        }));
+        sleep(Duration::from_millis(1)).await;
+    }
+    assert!(order.lock().is_empty());
+
+    // Cada liberação entrega a vaga a um único waiter, o mais antigo
+    noisy.send(()).unwrap();
+    for waiter in waiters {
+        waiter.await.unwrap().unwrap();
+    }
+    assert_eq!(*order.lock(), vec![0, 1, 2]);
+
+    quiet.send(()).unwrap();
+    noisy_handle.await.unwrap();
+    quiet_handle.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn global_queue_settings_apply_to_partitions() {
+    let guard: Guard = Arc::new(
+        PartitionedFlowGuard::from_guard(
+            FlowGuard::new(ManualLimit::new(2))
+                .with_max_queue(1)
+                .with_queue_timeout(Duration::from_millis(50)),
+        )
+        .with_partition("noisy", 1.0)
+        .with_partition("quiet", 1.0),
+    );
+    let (first, first_handle) = hold(&guard, "noisy").await;
+    let (second, second_handle) = hold(&guard, "noisy").await;
+
+    let waiter = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move { guard.run("noisy", async { Ok::<_, &str>(()) }).await })
+    };
+    sleep(Duration::from_millis(1)).await;
+
+    // A fila global já tem um waiter
+    assert!(matches!(
+        guard.run("noisy", async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::QueueFull)
+    ));
+    assert!(matches!(
+        waiter.await.unwrap(),
+        Err(FlowError::QueueTimeout)
+    ));
+
+    first.send(()).unwrap();
+    second.send(()).unwrap();
+    first_handle.await.unwrap();
+    second_handle.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn close_wakes_waiters_and_drain_waits_for_running() {
+    let guard = tenants(2);
+    let (first, first_handle) = hold(&guard, "noisy").await;
+    let (second, second_handle) = hold(&guard, "noisy").await;
+
+    let waiter = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move { guard.run("noisy", async { Ok::<_, &str>(()) }).await })
+    };
+    sleep(Duration::from_millis(1)).await;
+
+    guard.close();
+    assert!(matches!(waiter.await.unwrap(), Err(FlowError::Closed)));
+    assert!(matches!(
+        guard.try_run("quiet", async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::Closed)
+    ));
+
+    let drain = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move { guard.drain().await })
+    };
+    sleep(Duration::from_millis(1)).await;
+    assert!(!drain.is_finished());
+
+    first.send(()).unwrap();
+    second.send(()).unwrap();
+    first_handle.await.unwrap();
+    second_handle.await.unwrap();
+    timeout(Duration::from_secs(1), drain)
+        .await
+        .expect("drain should finish")
+        .unwrap();
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test(start_paused = true)]
+async fn layer_extracts_the_key_from_the_request() {
+    use flow_guard::{AcquireMode, PartitionedFlowGuardLayer};
+    use tower::{service_fn, Layer, Service, ServiceExt};
+
+    let guard = tenants(2);
+    let layer =
+        PartitionedFlowGuardLayer::new(Arc::clone(&guard), |req: &(&'static str, u64)| req.0)
+            .with_mode(AcquireMode::Shed);
+
+    let service = layer.layer(service_fn(|(_, millis): (&'static str, u64)| async move {
+        sleep(Duration::from_millis(millis)).await;
+        Ok::<_, &str>(())
+    }));
+
+    // "noisy" ocupa as 2 permissões por um tempo
+    let mut busy = Vec::new();
+    for _ in 0..2 {
+        let mut service = service.clone();
+        busy.push(tokio::spawn(async move {
+            service.ready().await.unwrap().call(("noisy", 100)).await
+        }));
+    }
+    sleep(Duration::from_millis(10)).await;
+
+    let mut service = service.clone();
+    assert!(matches!(
+        service.ready().await.unwrap().call(("noisy", 0)).await,
+        Err(FlowError::Dropped)
+    ));
+    assert!(service
+        .ready()
+        .await
+        .unwrap()
+        .call(("quiet", 0))
+        .await
+        .is_ok());
+
+    for handle in busy {
+        handle.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! Regressão: permissões precisam voltar para o semáforo REAL do FlowGuard.
+//!
+//! Antes, cada permissão liberava em uma cópia descartável do semáforo e o
+//! contador de permissões disponíveis nunca era reposto.
+
+use flow_guard::{FixedStrategy, FlowGuard, VegasStrategy};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+const LIMIT: usize = 8;
+
+#[tokio::test]
+async fn permits_return_after_thousands_of_sequential_runs() {
+    let guard = FlowGuard::new(FixedStrategy::new(LIMIT));
+
+    for i in 0..5_000 {
+        let result = guard.run(async move { Ok::<_, &str>(i) }).await;
+        assert_eq!(result.unwrap(), i);
+    }
+
+    assert_eq!(guard.available_permits(), LIMIT);
+}
+
+#[tokio::test]
+async fn permits_return_after_errors() {
+    let guard = FlowGuard::new(FixedStrategy::new(LIMIT));
+
+    for i in 0..2_000 {
+        let _ = guard
+            .run(async move {
+                if i % 2 == 0 {
+                    Ok(i)
+                } else {
+                    Err("falha simulada")
+                }
+            })
+            .await;
+    }
+
+    assert_eq!(guard.available_permits(), LIMIT);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn permits_return_after_concurrent_runs() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(LIMIT)));
+
+    let handles: Vec<_> = (0..2_000)
+        .map(|i| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move {
+                guard
+                    .run(async move {
+                        tokio::task::yield_now().await;
+                        Ok::<_, &str>(i)
+                    })
+                    .await
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert!(handle.await.unwrap().is_ok());
+    }
+
+    assert_eq!(guard.available_permits(), LIMIT);
+}
+
+#[tokio::test]
+async fn permits_return_after_panics() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(LIMIT)));
+
+    for _ in 0..200 {
+        let guard = Arc::clone(&guard);
+        let handle = tokio::spawn(async move {
+            guard
+                .run(async {
+                    tokio::task::yield_now().await;
+                    panic!("pânico dentro da tarefa protegida");
+                    #[allow(unreachable_code)]
+                    Ok::<(), &str>(())
+                })
+                .await
+        });
+
+        assert!(handle.await.unwrap_err().is_panic());
+    }
+
+    assert_eq!(guard.available_permits(), LIMIT);
+}
+
+#[tokio::test(start_paused = true)]
+async fn permits_return_after_cancelled_futures() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(LIMIT)));
+
+    // Cancela futures que já seguram a permissão
+    for _ in 0..500 {
+        let result = timeout(
+            Duration::from_micros(1),
+            guard.run(async {
+                sleep(Duration::from_secs(60)).await;
+                Ok::<_, &str>(())
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+    assert_eq!(guard.available_permits(), LIMIT);
+
+    // Ocupa todas as permissões e aborta tarefas ainda na fila de espera
+    let holders: Vec<_> = (0..LIMIT)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move {
+                guard
+                    .run(async {
+                        sleep(Duration::from_millis(50)).await;
+                        Ok::<_, &str>(())
+                    })
+                    .await
+            })
+        })
+        .collect();
+
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(guard.available_permits(), 0);
+
+    let waiters: Vec<_> = (0..100)
+        .map(|_| {
+            let guard = Arc::clone(&guard);
+            tokio::spawn(async move { guard.run(async { Ok::<_, &str>(()) }).await })
+        })
+        .collect();
+
+    for waiter in &waiters {
+        waiter.abort();
+    }
+    for holder in holders {
+        assert!(holder.await.unwrap().is_ok());
+    }
+
+    assert_eq!(guard.available_permits(), LIMIT);
+
+    // As permissões devolvidas continuam utilizáveis em paralelo
+    let results = futures_util::future::join_all((0..LIMIT).map(|_| {
+        guard.run(async {
+            sleep(Duration::from_millis(5)).await;
+            Ok::<_, &str>(())
+        })
+    }))
+    .await;
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(guard.available_permits(), LIMIT);
+}
+
+#[tokio::test]
+async fn vegas_guard_keeps_permits_in_sync_with_limit() {
+    let strategy = Arc::new(VegasStrategy::new(4));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    for _ in 0..3_000 {
+        guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    }
+
+    // Ocioso: todas as permissões do limite atual estão disponíveis
+    assert_eq!(guard.available_permits(), guard.current_limit());
+}
@@ -0,0 +1,81 @@
+//! Exportador no formato texto do Prometheus (feature `metrics-prometheus`).
+#![cfg(feature = "metrics-prometheus")]
+
+use flow_guard::prometheus::PrometheusExporter;
+use flow_guard::{FixedStrategy, FlowGuard};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn renders_every_registered_guard() {
+    let exporter = PrometheusExporter::new();
+    let db = FlowGuard::new(FixedStrategy::new(10));
+    let cache = FlowGuard::new(FixedStrategy::new(50));
+    exporter.register("db", &db);
+    exporter.register("cache", &cache);
+
+    for _ in 0..3 {
+        db.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    }
+    let _token = cache.acquire().await.unwrap();
+    assert!(db.try_acquire().is_ok());
+
+    let text = exporter.render();
+
+    assert!(text.contains("# TYPE flowguard_limit gauge"));
+    assert!(text.contains(r#"flowguard_limit{guard="db"} 10"#));
+    assert!(text.contains(r#"flowguard_limit{guard="cache"} 50"#));
+    assert!(text.contains(r#"flowguard_in_flight{guard="cache"} 1"#));
+    assert!(text.contains(r#"flowguard_queue_depth{guard="db"} 0"#));
+    assert!(text.contains("# TYPE flowguard_acquired_total counter"));
+    assert!(text.contains(r#"flowguard_acquired_total{guard="db"} 4"#));
+    assert!(text.contains(r#"flowguard_dropped_total{guard="db"} 0"#));
+    assert!(text.contains(r#"flowguard_rejected_total{guard="db"} 0"#));
+    assert!(text.contains(r#"flowguard_timed_out_total{guard="db"} 0"#));
+    assert!(text.contains("# TYPE flowguard_latency_seconds histogram"));
+    assert!(text.contains(r#"flowguard_latency_seconds_bucket{guard="db",le="0.001"}"#));
+    assert!(text.contains(r#"flowguard_latency_seconds_bucket{guard="db",le="+Inf"} 4"#));
+    assert!(text.contains(r#"flowguard_latency_seconds_count{guard="db"} 4"#));
+}
+
+#[test]
+fn escapes_label_values() {
+    let exporter = PrometheusExporter::new();
+    exporter.register("say \"hi\"\\", &FlowGuard::new(FixedStrategy::new(1)));
+
+    assert!(exporter
+        .render()
+        .contains(r#"flowguard_limit{guard="say \"hi\"\\"} 1"#));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn router_serves_metrics_endpoint() {
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    let exporter = Arc::new(PrometheusExporter::new());
+    exporter.register("api", &FlowGuard::new(FixedStrategy::new(7)));
+
+    let app = axum::Router::new().merge(Arc::clone(&exporter).router());
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain; version=0.0.4"));
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains(r#"flowguard_limit{guard="api"} 7"#));
+}
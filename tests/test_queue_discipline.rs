@@ -0,0 +1,110 @@
+//! Disciplina da fila de espera: FIFO, LIFO e LIFO adaptativo.
+
+use flow_guard::{Criticality, FixedStrategy, FlowGuard, QueueDiscipline};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+type Order = Arc<Mutex<Vec<&'static str>>>;
+
+/// Enfileira `names` (nessa ordem de chegada) atrás de uma permissão ocupada e
+/// devolve a ordem em que foram atendidos.
+async fn served_order(
+    guard: FlowGuard<FixedStrategy>,
+    names: &[(Criticality, &'static str)],
+    hold: Duration,
+) -> Vec<&'static str> {
+    let guard = Arc::new(guard);
+    let order = Order::default();
+    let holder = guard.acquire().await.unwrap();
+
+    let mut handles = Vec::new();
+    for &(criticality, name) in names {
+        let guard = Arc::clone(&guard);
+        let order = Arc::clone(&order);
+        handles.push(tokio::spawn(async move {
+            guard
+                .run_with_criticality(criticality, async {
+                    order.lock().push(name);
+                    Ok::<_, &str>(())
+                })
+                .await
+                .unwrap();
+        }));
+        sleep(Duration::from_millis(2)).await;
+    }
+
+    sleep(hold).await;
+    holder.success();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let order = order.lock().clone();
+    order
+}
+
+const ABC: [(Criticality, &str); 3] = [
+    (Criticality::Normal, "a"),
+    (Criticality::Normal, "b"),
+    (Criticality::Normal, "c"),
+];
+
+#[tokio::test(start_paused = true)]
+async fn fifo_is_the_default() {
+    let order = served_order(FlowGuard::new(FixedStrategy::new(1)), &ABC, Duration::ZERO).await;
+
+    assert_eq!(order, ["a", "b", "c"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn lifo_serves_the_newest_first() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_queue_discipline(QueueDiscipline::Lifo);
+
+    let order = served_order(guard, &ABC, Duration::ZERO).await;
+
+    assert_eq!(order, ["c", "b", "a"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn lifo_still_respects_criticality() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_queue_discipline(QueueDiscipline::Lifo);
+    let names = [
+        (Criticality::High, "high-1"),
+        (Criticality::High, "high-2"),
+        (Criticality::Normal, "normal"),
+    ];
+
+    let order = served_order(guard, &names, Duration::ZERO).await;
+
+    assert_eq!(order, ["high-2", "high-1", "normal"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn adaptive_lifo_is_fifo_while_the_queue_is_short_lived() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_queue_discipline(
+        QueueDiscipline::AdaptiveLifo {
+            threshold: Duration::from_secs(5),
+        },
+    );
+
+    let order = served_order(guard, &ABC, Duration::ZERO).await;
+
+    assert_eq!(order, ["a", "b", "c"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn adaptive_lifo_switches_once_the_oldest_waits_too_long() {
+    let guard = FlowGuard::new(FixedStrategy::new(1)).with_queue_discipline(
+        QueueDiscipline::AdaptiveLifo {
+            threshold: Duration::from_millis(20),
+        },
+    );
+
+    // "a" espera mais que o limiar: "c" é atendido primeiro. Em seguida "a"
+    // continua acima do limiar, então "b" (o mais novo restante) vem antes
+    let order = served_order(guard, &ABC, Duration::from_millis(30)).await;
+
+    assert_eq!(order, ["c", "b", "a"]);
+}
@@ -0,0 +1,261 @@
+#![cfg(feature = "serde")]
+//! Recarga a quente: `update_config`, `FlowGuard::reload_from` e `config::watch_file`.
+
+use flow_guard::config::{watch_file, AimdConfig, FixedConfig, VegasConfig};
+use flow_guard::{
+    AimdStrategy, ConfigError, FlowGuard, FlowGuardConfig, LimitStrategy, ReloadableStrategy,
+    StrategyConfig, VegasStrategy,
+};
+use std::time::Duration;
+use tokio::sync::watch;
+
+fn parse(text: &str) -> FlowGuardConfig {
+    toml::from_str(text).unwrap()
+}
+
+/// Espera até `condition` valer, verificando a cada 5ms.
+async fn eventually(mut condition: impl FnMut() -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("condition not reached in time");
+}
+
+#[test]
+fn vegas_bounds_apply_immediately() {
+    let strategy = VegasStrategy::new(50);
+
+    strategy
+        .update_config(&StrategyConfig::Vegas(VegasConfig {
+            max_limit: Some(20),
+            ..VegasConfig::default()
+        }))
+        .unwrap();
+    assert_eq!(strategy.current_limit(), 20);
+
+    // Limite dentro dos novos limites é preservado
+    strategy
+        .update_config(&StrategyConfig::Vegas(VegasConfig {
+            max_limit: Some(100),
+            ..VegasConfig::default()
+        }))
+        .unwrap();
+    assert_eq!(strategy.current_limit(), 20);
+
+    strategy
+        .update_config(&StrategyConfig::Vegas(VegasConfig {
+            min_limit: 40,
+            max_limit: Some(100),
+            ..VegasConfig::default()
+        }))
+        .unwrap_err();
+    assert_eq!(
+        strategy.current_limit(),
+        20,
+        "invalid config must not apply"
+    );
+}
+
+#[test]
+fn vegas_alpha_and_beta_change_the_adjustment() {
+    let strategy = VegasStrategy::new(10);
+    strategy.on_success(Duration::from_millis(10));
+    let limit = strategy.current_limit();
+
+    // Fila estimada de ~5 (10 * (1 - 10/20)): com beta 4 o limite cai...
+    strategy.on_success(Duration::from_millis(20));
+    assert_eq!(strategy.current_limit(), limit - 1);
+
+    // ...e com alpha 8 / beta 16 passa a crescer
+    strategy
+        .update_config(&StrategyConfig::Vegas(VegasConfig {
+            alpha: 8.0.into(),
+            beta: 16.0.into(),
+            ..VegasConfig::default()
+        }))
+        .unwrap();
+    strategy.on_success(Duration::from_millis(20));
+    assert_eq!(strategy.current_limit(), limit);
+}
+
+#[test]
+fn aimd_backoff_can_be_tightened() {
+    let strategy = AimdStrategy::new(100);
+    strategy
+        .update_config(&StrategyConfig::Aimd(AimdConfig {
+            initial_limit: 100,
+            backoff_ratio: 0.5,
+            ..AimdConfig::default()
+        }))
+        .unwrap();
+
+    strategy.on_error();
+    assert_eq!(strategy.current_limit(), 50);
+}
+
+#[test]
+fn switching_algorithms_is_rejected() {
+    let strategy = VegasStrategy::new(10);
+    assert_eq!(
+        strategy.update_config(&StrategyConfig::Fixed(FixedConfig { limit: 3 })),
+        Err(ConfigError::StrategyMismatch {
+            expected: "vegas",
+            found: "fixed"
+        })
+    );
+}
+
+#[tokio::test]
+async fn guard_reload_resizes_the_semaphore() {
+    let guard =
+        FlowGuard::from_config(&parse("strategy = { type = \"fixed\", limit = 1 }")).unwrap();
+    let held = guard.acquire().await.unwrap();
+    assert!(guard.try_acquire().is_err());
+
+    let mut limits = guard.subscribe();
+    guard
+        .update_config(&parse("strategy = { type = \"fixed\", limit = 3 }"))
+        .unwrap();
+
+    assert_eq!(guard.current_limit(), 3);
+    assert!(limits.has_changed().unwrap());
+    assert_eq!(limits.borrow_and_update().limit, 3);
+    let _second = guard.try_acquire().unwrap();
+    drop(held);
+}
+
+#[tokio::test]
+async fn windowed_reload_reaches_the_inner_strategy() {
+    let guard = FlowGuard::from_config(&parse(
+        r#"
+        [strategy]
+        type = "windowed"
+        [strategy.inner]
+        type = "fixed"
+        limit = 4
+        "#,
+    ))
+    .unwrap();
+
+    guard
+        .update_config(&parse(
+            r#"
+            [strategy]
+            type = "windowed"
+            samples = 10
+            [strategy.inner]
+            type = "fixed"
+            limit = 2
+            "#,
+        ))
+        .unwrap();
+    assert_eq!(guard.current_limit(), 2);
+
+    let mismatch = parse(
+        r#"
+        [strategy]
+        type = "windowed"
+        [strategy.inner]
+        type = "aimd"
+        "#,
+    );
+    assert!(matches!(
+        guard.update_config(&mismatch),
+        Err(ConfigError::StrategyMismatch {
+            expected: "fixed",
+            found: "aimd"
+        })
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn reload_from_applies_published_configs() {
+    let initial = parse("strategy = { type = \"fixed\", limit = 5 }");
+    let (sender, configs) = watch::channel(initial.clone());
+
+    let first = FlowGuard::from_config(&initial).unwrap();
+    let second = FlowGuard::from_config(&initial).unwrap();
+    first.reload_from(configs.clone());
+    let task = second.reload_from(configs);
+
+    sender.send_replace(parse("strategy = { type = \"fixed\", limit = 2 }"));
+    eventually(|| first.current_limit() == 2 && second.current_limit() == 2).await;
+
+    // Configuração inválida é ignorada; a seguinte volta a ser aplicada
+    sender.send_replace(parse("strategy = { type = \"fixed\", limit = 0 }"));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(first.current_limit(), 2);
+
+    sender.send_replace(parse("strategy = { type = \"fixed\", limit = 8 }"));
+    eventually(|| first.current_limit() == 8).await;
+
+    drop(sender);
+    task.await.unwrap();
+}
+
+#[tokio::test]
+async fn watch_file_publishes_valid_changes() {
+    let path = std::env::temp_dir().join(format!("flowguard-reload-{}.toml", std::process::id()));
+    std::fs::write(&path, "strategy = { type = \"fixed\", limit = 5 }").unwrap();
+
+    let mut configs = watch_file(&path, Duration::from_millis(10), |text| {
+        toml::from_str::<FlowGuardConfig>(text)
+    })
+    .await
+    .unwrap();
+    let guard = FlowGuard::from_config(&configs.borrow_and_update()).unwrap();
+    guard.reload_from(configs.clone());
+
+    std::fs::write(&path, "strategy = { type = \"fixed\", limit = 9 }").unwrap();
+    eventually(|| guard.current_limit() == 9).await;
+    assert!(configs.has_changed().unwrap());
+    configs.borrow_and_update();
+
+    // Arquivo quebrado não é publicado
+    std::fs::write(&path, "strategy = {").unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!configs.has_changed().unwrap());
+    assert_eq!(guard.current_limit(), 9);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn closing_the_guard_stops_reloading_and_polling() {
+    let path = std::env::temp_dir().join(format!("flowguard-close-{}.toml", std::process::id()));
+    std::fs::write(&path, "strategy = { type = \"fixed\", limit = 5 }").unwrap();
+
+    let configs = watch_file(&path, Duration::from_millis(10), |text| {
+        toml::from_str::<FlowGuardConfig>(text)
+    })
+    .await
+    .unwrap();
+    let guard = FlowGuard::from_config(&configs.borrow()).unwrap();
+    let reload = guard.reload_from(configs);
+
+    // Sem o close, a tarefa seguraria o receiver e o polling para sempre
+    guard.close();
+    tokio::time::timeout(Duration::from_secs(5), reload)
+        .await
+        .expect("reload task should stop when the guard is closed")
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn watch_file_fails_on_invalid_initial_contents() {
+    let path = std::env::temp_dir().join(format!("flowguard-invalid-{}.toml", std::process::id()));
+    std::fs::write(&path, "strategy = {").unwrap();
+
+    let result = watch_file(&path, Duration::from_millis(10), |text| {
+        toml::from_str::<FlowGuardConfig>(text)
+    })
+    .await;
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,135 @@
+//! Simulação determinística com tempo virtual (feature `sim`).
+#![cfg(feature = "sim")]
+
+use flow_guard::sim::{Backend, Scenario, ServiceTime};
+use flow_guard::{AimdStrategy, FixedStrategy, FlowGuard, VegasStrategy};
+use std::time::Duration;
+
+/// 20 workers com ~20ms de serviço: capacidade de ~1000 req/s.
+fn backend() -> Backend {
+    Backend::new(
+        20,
+        ServiceTime::Uniform(Duration::from_millis(15), Duration::from_millis(25)),
+    )
+}
+
+fn overload() -> Scenario {
+    Scenario::new(backend())
+        .with_arrival_rate(1500.0)
+        .with_duration(Duration::from_secs(5))
+}
+
+#[test]
+fn same_seed_gives_the_same_report() {
+    let scenario = overload();
+
+    let first = scenario.simulate(FlowGuard::new(VegasStrategy::new(10)));
+    let second = scenario.simulate(FlowGuard::new(VegasStrategy::new(10)));
+    let other_seed = scenario
+        .clone()
+        .with_seed(42)
+        .simulate(FlowGuard::new(VegasStrategy::new(10)));
+
+    assert_eq!(first, second);
+    assert_ne!(first.offered, other_seed.offered);
+}
+
+#[test]
+fn adaptive_limit_bounds_latency_under_overload() {
+    let guard = |strategy| FlowGuard::new(strategy).with_queue_timeout(Duration::from_millis(200));
+    let scenario = overload();
+
+    let unlimited = scenario.simulate(FlowGuard::new(FixedStrategy::new(100_000)));
+    let aimd = scenario.simulate(guard(AimdStrategy::new(10)));
+
+    // Sem limite tudo é aceito e a fila do backend cresce sem parar
+    assert_eq!(unlimited.rejected, 0);
+    assert!(unlimited.latency.p99 > Duration::from_secs(1));
+
+    // Com limite o excesso é recusado e a latência fica limitada pela fila
+    // do guard
+    assert!(aimd.rejected > 0);
+    assert!(aimd.latency.p99 < Duration::from_millis(500));
+    assert!(aimd.goodput > 800.0, "goodput {}", aimd.goodput);
+}
+
+#[test]
+fn underload_is_served_without_rejections() {
+    let report = Scenario::new(backend())
+        .with_arrival_rate(200.0)
+        .with_duration(Duration::from_secs(5))
+        .simulate(FlowGuard::new(VegasStrategy::new(10)));
+
+    assert_eq!(report.rejected, 0);
+    assert_eq!(report.succeeded, report.offered);
+    assert!(
+        (report.goodput - 200.0).abs() < 30.0,
+        "goodput {}",
+        report.goodput
+    );
+    assert!(report.latency.p50 >= Duration::from_millis(15));
+    assert!(report.latency.max <= Duration::from_millis(25));
+}
+
+#[test]
+fn failures_are_reported_separately() {
+    let report = Scenario::new(backend().with_failure_rate(0.1))
+        .with_arrival_rate(200.0)
+        .with_duration(Duration::from_secs(5))
+        .simulate(FlowGuard::new(FixedStrategy::new(100)));
+
+    let rate = report.failed as f64 / report.offered as f64;
+    assert!((0.05..0.15).contains(&rate), "failure rate {rate}");
+    assert_eq!(report.succeeded + report.failed, report.offered);
+}
+
+#[test]
+fn full_backend_queue_fails_fast() {
+    let report = Scenario::new(backend().with_max_queue(10))
+        .with_arrival_rate(1500.0)
+        .with_duration(Duration::from_secs(2))
+        .simulate(FlowGuard::new(FixedStrategy::new(100_000)));
+
+    assert!(report.failed > 0);
+    assert!(report.latency.max < Duration::from_millis(500));
+}
+
+#[test]
+fn limit_trace_is_sampled_over_time() {
+    let report = Scenario::new(backend())
+        .with_duration(Duration::from_secs(2))
+        .with_sample_interval(Duration::from_millis(250))
+        .simulate(FlowGuard::new(AimdStrategy::new(7)));
+
+    assert_eq!(report.limit_trace.len(), 9);
+    assert_eq!(report.limit_trace[0], (Duration::ZERO, 7));
+    assert_eq!(report.limit_trace[8].0, Duration::from_secs(2));
+}
+
+#[test]
+#[should_panic(expected = "arrival rate")]
+fn rejects_zero_arrival_rate() {
+    let _ = Scenario::new(backend()).with_arrival_rate(0.0);
+}
+
+#[test]
+#[should_panic(expected = "arrival rate")]
+fn rejects_infinite_arrival_rate() {
+    let _ = Scenario::new(backend()).with_arrival_rate(f64::INFINITY);
+}
+
+#[test]
+#[should_panic(expected = "backend capacity")]
+fn rejects_backend_without_capacity() {
+    let _ = Backend::new(0, ServiceTime::Constant(Duration::from_millis(10)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn runs_inside_a_paused_runtime() {
+    let report = Scenario::new(backend())
+        .with_duration(Duration::from_secs(1))
+        .run(FlowGuard::new(VegasStrategy::new(10)))
+        .await;
+
+    assert!(report.succeeded > 0);
+}
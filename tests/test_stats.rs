@@ -0,0 +1,87 @@
+//! Contadores e histograma de latência de `FlowGuard::stats()` (feature
+//! `metrics-prometheus`).
+#![cfg(feature = "metrics-prometheus")]
+
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+#[tokio::test(start_paused = true)]
+async fn counts_acquired_dropped_rejected_and_timed_out() {
+    let guard = Arc::new(
+        FlowGuard::new(FixedStrategy::new(1))
+            .with_max_queue(1)
+            .with_queue_timeout(Duration::from_millis(20)),
+    );
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let holder = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move {
+            guard
+                .run(async move {
+                    let _ = rx.await;
+                    Ok::<_, &str>(())
+                })
+                .await
+        })
+    };
+    sleep(Duration::from_millis(5)).await;
+
+    // Sem permissão livre: try_run descarta
+    assert!(matches!(
+        guard.try_run(async { Ok::<_, &str>(()) }).await,
+        Err(FlowError::Dropped)
+    ));
+
+    // Um waiter ocupa a fila e estoura o prazo; o seguinte encontra a fila cheia
+    let (timed_out, rejected) = tokio::join!(guard.run(async { Ok::<_, &str>(()) }), async {
+        sleep(Duration::from_millis(5)).await;
+        guard.run(async { Ok::<_, &str>(()) }).await
+    });
+    assert!(matches!(timed_out, Err(FlowError::QueueTimeout)));
+    assert!(matches!(rejected, Err(FlowError::QueueFull)));
+
+    tx.send(()).unwrap();
+    holder.await.unwrap().unwrap();
+
+    let stats = guard.stats();
+    assert_eq!(stats.acquired, 1);
+    assert_eq!(stats.dropped, 1);
+    assert_eq!(stats.rejected, 1);
+    assert_eq!(stats.timed_out, 1);
+}
+
+#[tokio::test]
+async fn closed_guard_counts_as_rejected() {
+    let guard = FlowGuard::new(FixedStrategy::new(1));
+    guard.close();
+
+    let _ = guard.run(async { Ok::<_, &str>(()) }).await;
+
+    assert_eq!(guard.stats().rejected, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn latency_histogram_is_cumulative() {
+    let guard = FlowGuard::new(FixedStrategy::new(4));
+
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    guard
+        .run(async {
+            sleep(Duration::from_millis(30)).await;
+            Ok::<_, &str>(())
+        })
+        .await
+        .unwrap();
+    let token = guard.acquire().await.unwrap();
+    token.ignore();
+
+    let latency = guard.stats().latency;
+    assert_eq!(latency.count, 3);
+    assert_eq!(*latency.buckets.last().unwrap(), 3);
+    assert!(latency.buckets.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(latency.sum >= Duration::from_millis(30));
+}
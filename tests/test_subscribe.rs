@@ -0,0 +1,94 @@
+//! Assinatura de mudanças de limite com `FlowGuard::subscribe()`.
+
+mod common;
+
+use common::ManualLimit;
+use flow_guard::stats::RECENT_RTTS;
+use flow_guard::FlowGuard;
+use std::time::Duration;
+
+#[tokio::test]
+async fn initial_value_reflects_current_state() {
+    let guard = FlowGuard::new(ManualLimit::new(8));
+    let _token = guard.acquire().await.unwrap();
+
+    let limits = guard.subscribe();
+
+    let snapshot = limits.borrow();
+    assert_eq!(snapshot.limit, 8);
+    assert_eq!(snapshot.in_flight, 1);
+    assert_eq!(snapshot.queue_len, 0);
+    assert!(snapshot.recent_rtts.is_empty());
+}
+
+#[tokio::test]
+async fn publishes_when_limit_changes() {
+    let guard = FlowGuard::new(ManualLimit::new(8));
+    let mut limits = guard.subscribe();
+
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    // Sucessos não mudam o limite: nada é publicado
+    assert!(!limits.has_changed().unwrap());
+
+    let _ = guard.run(async { Err::<(), _>("overloaded") }).await;
+
+    assert!(limits.has_changed().unwrap());
+    let snapshot = limits.borrow_and_update().clone();
+    assert_eq!(snapshot.limit, 7);
+    assert_eq!(snapshot.in_flight, 1);
+    // As amostras dos sucessos anteriores acompanham a mudança
+    assert_eq!(snapshot.recent_rtts.len(), 2);
+}
+
+#[tokio::test]
+async fn token_outcomes_publish_too() {
+    let guard = FlowGuard::new(ManualLimit::new(8));
+    let mut limits = guard.subscribe();
+
+    let waiter = tokio::spawn(async move {
+        limits.changed().await.unwrap();
+        let limit = limits.borrow().limit;
+        limit
+    });
+
+    guard.acquire().await.unwrap().dropped();
+
+    assert_eq!(waiter.await.unwrap(), 7);
+}
+
+#[tokio::test]
+async fn keeps_only_the_most_recent_rtts() {
+    let guard = FlowGuard::new(ManualLimit::new(8));
+    let mut limits = guard.subscribe();
+
+    for _ in 0..RECENT_RTTS + 5 {
+        guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    }
+    let _ = guard.run(async { Err::<(), _>("overloaded") }).await;
+
+    assert_eq!(limits.borrow_and_update().recent_rtts.len(), RECENT_RTTS);
+}
+
+#[tokio::test(start_paused = true)]
+async fn recent_rtts_are_ordered_oldest_first() {
+    let guard = FlowGuard::new(ManualLimit::new(8));
+    let mut limits = guard.subscribe();
+
+    let total = RECENT_RTTS as u64 + 5;
+    for millis in 1..=total {
+        guard
+            .run(async {
+                tokio::time::sleep(Duration::from_millis(millis)).await;
+                Ok::<_, &str>(())
+            })
+            .await
+            .unwrap();
+    }
+    let _ = guard.run(async { Err::<(), _>("overloaded") }).await;
+
+    let expected: Vec<Duration> = (total - RECENT_RTTS as u64 + 1..=total)
+        .map(Duration::from_millis)
+        .collect();
+    assert_eq!(limits.borrow_and_update().recent_rtts, expected);
+}
@@ -0,0 +1,197 @@
+//! Gravação de traces com `TraceRecorder` e replay offline das estratégias.
+
+use flow_guard::trace::{read_trace, replay, TraceRecorder, TraceSample};
+use flow_guard::{
+    AimdStrategy, FlowGuard, LimitStrategy, Outcome, TraceError, VegasStrategy, WindowedStrategy,
+};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Buffer em memória que continua acessível depois de entregue ao recorder.
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuf {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn sample(ms: u64, latency_ms: u64, outcome: Outcome, in_flight: usize) -> TraceSample {
+    TraceSample {
+        timestamp: Duration::from_millis(ms),
+        latency: Duration::from_millis(latency_ms),
+        outcome,
+        in_flight,
+    }
+}
+
+#[test]
+fn written_samples_round_trip() {
+    let buf = SharedBuf::default();
+    let recorder = TraceRecorder::new(buf.clone()).unwrap();
+    let samples = vec![
+        sample(0, 20, Outcome::Success, 1),
+        sample(5, 250, Outcome::Dropped, 4),
+        sample(9, 1, Outcome::Ignore, 2),
+    ];
+    for s in &samples {
+        recorder.write(*s);
+    }
+    recorder.flush().unwrap();
+
+    let csv = buf.contents();
+    assert!(csv.starts_with("timestamp_us,latency_us,outcome,in_flight\n"));
+    assert!(csv.contains("5000,250000,dropped,4\n"));
+
+    assert_eq!(read_trace(csv.as_bytes()).unwrap(), samples);
+}
+
+#[test]
+fn header_is_optional() {
+    let samples = read_trace("10,2000,success,3\n\n".as_bytes()).unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].latency, Duration::from_millis(2));
+    assert_eq!(samples[0].in_flight, 3);
+}
+
+#[test]
+fn malformed_lines_report_their_number() {
+    let csv = "timestamp_us,latency_us,outcome,in_flight\n0,100,success,1\n1,100,timeout,1\n";
+    match read_trace(csv.as_bytes()) {
+        Err(TraceError::Parse { line, reason }) => {
+            assert_eq!(line, 3);
+            assert!(reason.contains("timeout"), "{reason}");
+        }
+        other => panic!("expected parse error, got {other:?}"),
+    }
+
+    match read_trace("0,100,success\n".as_bytes()) {
+        Err(TraceError::Parse { line: 1, reason }) => assert!(reason.contains("4 fields")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+
+    assert!(matches!(
+        read_trace("0,abc,success,1\n".as_bytes()),
+        Err(TraceError::Parse { line: 1, .. })
+    ));
+}
+
+#[test]
+fn replay_records_each_limit_change() {
+    let samples = vec![
+        sample(0, 10, Outcome::Success, 1),
+        sample(10, 10, Outcome::Success, 2),
+        sample(20, 10, Outcome::Ignore, 2),
+        sample(30, 500, Outcome::Dropped, 3),
+    ];
+    let strategy = AimdStrategy::new(10);
+
+    let timeline = replay(&strategy, &samples);
+
+    // AIMD: +1 por sucesso, x0.9 no erro (12 -> 10); o Ignore não conta
+    let points: Vec<_> = timeline
+        .iter()
+        .map(|p| (p.timestamp.as_millis(), p.limit, p.in_flight))
+        .collect();
+    assert_eq!(points, [(0, 10, 0), (0, 11, 1), (10, 12, 2), (30, 10, 3)]);
+    assert_eq!(timeline.last().unwrap().limit, strategy.current_limit());
+}
+
+#[test]
+fn replay_without_changes_yields_only_the_initial_point() {
+    let samples = vec![sample(0, 10, Outcome::Ignore, 1)];
+    let timeline = replay(&AimdStrategy::new(7), &samples);
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(timeline[0].limit, 7);
+}
+
+#[test]
+fn replay_advances_the_clock_to_each_sample() {
+    // Uma amostra a cada 10ms durante 1s: só a janela de 100ms fecha
+    let samples: Vec<_> = (0..100)
+        .map(|i| sample(i * 10, 10, Outcome::Success, 1))
+        .collect();
+    let strategy = WindowedStrategy::new(AimdStrategy::new(10))
+        .with_sample_window(1000)
+        .with_time_window(Duration::from_millis(100));
+
+    let timeline = replay(&strategy, &samples);
+
+    let points: Vec<_> = timeline
+        .iter()
+        .map(|p| (p.timestamp.as_millis(), p.limit))
+        .collect();
+    assert_eq!(points[..3], [(0, 10), (100, 11), (210, 12)]);
+    assert_eq!(strategy.current_limit(), 19);
+}
+
+#[test]
+fn replay_lets_vegas_probes_finish() {
+    // 10s de trace: uma amostra de 20ms a cada 5ms
+    let samples: Vec<_> = (0..2000)
+        .map(|i| sample(i * 5, 20, Outcome::Success, 4))
+        .collect();
+    let strategy = VegasStrategy::builder()
+        .initial_limit(10)
+        .probe_interval(20)
+        .probe_seed(1)
+        .build()
+        .unwrap();
+
+    let timeline = replay(&strategy, &samples);
+
+    // Cada sondagem derruba o limite para 1 e o restaura depois de medir
+    // execuções admitidas após a queda
+    let probes = timeline.iter().filter(|p| p.limit == 1).count();
+    assert!(probes > 1, "{timeline:?}");
+    assert!(timeline.last().unwrap().limit > 1, "{timeline:?}");
+    assert!(!strategy.is_probing());
+    assert_eq!(strategy.base_rtt(), Duration::from_millis(20));
+}
+
+#[tokio::test]
+async fn guard_records_every_execution() {
+    let buf = SharedBuf::default();
+    let recorder = Arc::new(TraceRecorder::new(buf.clone()).unwrap());
+    let guard = FlowGuard::new(AimdStrategy::new(5)).with_trace_recorder(recorder.clone());
+
+    guard.run(async { Ok::<_, ()>(()) }).await.unwrap();
+    let _ = guard.run(async { Err::<(), _>("boom") }).await;
+    {
+        let _held = guard.acquire().await.unwrap();
+        guard.run(async { Ok::<_, ()>(()) }).await.unwrap();
+    }
+    recorder.flush().unwrap();
+
+    let samples = read_trace(buf.contents().as_bytes()).unwrap();
+    let outcomes: Vec<_> = samples.iter().map(|s| s.outcome).collect();
+    assert_eq!(
+        outcomes,
+        [
+            Outcome::Success,
+            Outcome::Dropped,
+            Outcome::Success,
+            Outcome::Success
+        ]
+    );
+    // A execução dentro do bloco roda junto com o token segurado
+    assert_eq!(samples[0].in_flight, 1);
+    assert_eq!(samples[2].in_flight, 2);
+    assert!(samples.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+    // O que foi gravado alimenta o replay de outra estratégia
+    let timeline = replay(&AimdStrategy::new(5), &samples);
+    assert_eq!(timeline.last().unwrap().limit, guard.current_limit());
+}
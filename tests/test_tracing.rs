@@ -0,0 +1,191 @@
+//! Nome do guard, eventos de mudança de limite e span por execução.
+
+use flow_guard::{AimdStrategy, FlowGuard, GradientStrategy, VegasStrategy};
+use parking_lot::Mutex;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Writer que acumula a saída do subscriber em memória.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+    fn output(&self) -> String {
+        String::from_utf8(self.0.lock().clone()).unwrap()
+    }
+}
+
+impl io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    type Writer = Capture;
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+fn subscriber(capture: &Capture) -> tracing::subscriber::DefaultGuard {
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(capture.clone())
+        .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .finish();
+    tracing::subscriber::set_default(subscriber)
+}
+
+#[test]
+fn guard_has_a_name() {
+    assert_eq!(FlowGuard::new(VegasStrategy::new(10)).name(), "flow_guard");
+    assert_eq!(
+        FlowGuard::new(VegasStrategy::new(10))
+            .with_name("db")
+            .name(),
+        "db"
+    );
+}
+
+#[tokio::test]
+async fn limit_change_emits_single_debug_event() {
+    let capture = Capture::default();
+    let _default = subscriber(&capture);
+    let guard = FlowGuard::new(VegasStrategy::new(10)).with_name("db");
+
+    let _ = guard.run(async { Err::<(), _>("overloaded") }).await;
+
+    let output = capture.output();
+    let events: Vec<&str> = output
+        .lines()
+        .filter(|line| line.contains("limit changed"))
+        .collect();
+    assert_eq!(events.len(), 1, "{output}");
+    let event = events[0];
+    assert!(event.contains("concurrency limit changed"), "{event}");
+    assert!(event.contains("guard=db"), "{event}");
+    assert!(event.contains("reason=\"error\""), "{event}");
+    assert!(event.contains("old_limit=10"), "{event}");
+    assert!(event.contains("new_limit=7"), "{event}");
+    assert!(event.contains("outcome=\"dropped\""), "{event}");
+}
+
+#[tokio::test]
+async fn vegas_events_carry_rtt_figures() {
+    let capture = Capture::default();
+    let _default = subscriber(&capture);
+    let guard = FlowGuard::new(VegasStrategy::new(10));
+
+    // RTT base inicial de 1s: uma resposta rápida indica fila vazia e o
+    // limite cresce
+    guard
+        .run(async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok::<_, &str>(())
+        })
+        .await
+        .unwrap();
+
+    let output = capture.output();
+    assert!(output.contains("reason=\"queue_below_alpha\""), "{output}");
+    assert!(output.contains("rtt_us="), "{output}");
+    assert!(output.contains("base_rtt_us="), "{output}");
+    assert!(output.contains("queue="), "{output}");
+}
+
+#[tokio::test(start_paused = true)]
+async fn aimd_events_explain_slow_successes() {
+    let capture = Capture::default();
+    let _default = subscriber(&capture);
+    let guard = FlowGuard::new(AimdStrategy::new(10).with_timeout(Duration::from_millis(1)));
+
+    // Sucesso mais lento que o timeout: o limite cai mesmo com `outcome=success`
+    guard
+        .run(async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok::<_, &str>(())
+        })
+        .await
+        .unwrap();
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+
+    let output = capture.output();
+    let events: Vec<&str> = output
+        .lines()
+        .filter(|line| line.contains("limit changed"))
+        .collect();
+    assert_eq!(events.len(), 2, "{output}");
+    assert!(events[0].contains("outcome=\"success\""), "{}", events[0]);
+    assert!(events[0].contains("reason=\"timeout\""), "{}", events[0]);
+    assert!(
+        events[1].contains("reason=\"additive_increase\""),
+        "{}",
+        events[1]
+    );
+}
+
+#[tokio::test]
+async fn gradient_events_carry_gradient_and_long_rtt() {
+    let capture = Capture::default();
+    let _default = subscriber(&capture);
+    let guard = FlowGuard::new(GradientStrategy::new(10));
+
+    let _ = guard.run(async { Err::<(), _>("overloaded") }).await;
+
+    let output = capture.output();
+    let event = output
+        .lines()
+        .find(|line| line.contains("limit changed"))
+        .unwrap_or_else(|| panic!("no limit change: {output}"));
+    assert!(event.contains("reason=\"error\""), "{event}");
+    assert!(event.contains("gradient=0.5"), "{event}");
+    assert!(event.contains("long_rtt_us="), "{event}");
+}
+
+#[tokio::test]
+async fn run_span_records_queue_wait_and_execution_time() {
+    let capture = Capture::default();
+    let _default = subscriber(&capture);
+    let guard = FlowGuard::new(VegasStrategy::new(10))
+        .with_name("cache")
+        .with_tracing_span();
+
+    guard
+        .run(async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok::<_, &str>(())
+        })
+        .await
+        .unwrap();
+
+    let output = capture.output();
+    // O span `limit_update` também fecha, aninhado no da execução
+    let close = output
+        .lines()
+        .find(|line| line.contains("close") && !line.contains("limit_update"))
+        .unwrap_or_else(|| panic!("span not closed: {output}"));
+    assert!(close.contains("flow_guard{guard=cache"), "{close}");
+    assert!(close.contains("queue_wait_us="), "{close}");
+    assert!(close.contains("exec_us="), "{close}");
+}
+
+#[tokio::test]
+async fn no_span_unless_enabled() {
+    let capture = Capture::default();
+    let _default = subscriber(&capture);
+    let guard = FlowGuard::new(VegasStrategy::new(10));
+
+    guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+
+    let output = capture.output();
+    assert!(!output.contains("flow_guard{"), "{output}");
+}
@@ -0,0 +1,90 @@
+//! `FlowGuard::try_run` e `AcquireMode::Shed`: descarte imediato sob carga.
+
+use flow_guard::{FixedStrategy, FlowError, FlowGuard};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn try_run_executes_when_permit_is_free() {
+    let guard = FlowGuard::new(FixedStrategy::new(1));
+
+    let result = guard.try_run(async { Ok::<_, &str>("ok") }).await;
+
+    assert_eq!(result.unwrap(), "ok");
+    assert_eq!(guard.available_permits(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn try_run_drops_immediately_when_saturated() {
+    let guard = Arc::new(FlowGuard::new(FixedStrategy::new(1)));
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let holder = {
+        let guard = Arc::clone(&guard);
+        tokio::spawn(async move {
+            guard
+                .run(async move {
+                    let _ = rx.await;
+                    Ok::<_, &str>(())
+                })
+                .await
+        })
+    };
+    sleep(Duration::from_millis(10)).await;
+
+    let started = std::time::Instant::now();
+    let result = guard.try_run(async { Ok::<_, &str>(()) }).await;
+    assert!(matches!(result, Err(FlowError::Dropped)));
+    assert!(started.elapsed() < Duration::from_millis(50));
+
+    tx.send(()).unwrap();
+    holder.await.unwrap().unwrap();
+
+    // Com a permissão devolvida, o try_run volta a passar
+    assert!(guard.try_run(async { Ok::<_, &str>(()) }).await.is_ok());
+}
+
+#[cfg(all(feature = "axum", feature = "tower"))]
+#[tokio::test(start_paused = true)]
+async fn shed_layer_answers_503_when_saturated() {
+    use axum::{
+        error_handling::HandleErrorLayer, http::StatusCode, response::IntoResponse, routing::get,
+        Router,
+    };
+    use flow_guard::{AcquireMode, FlowGuardLayer};
+    use tower::{ServiceBuilder, ServiceExt};
+
+    let layer = FlowGuardLayer::new(FixedStrategy::new(1)).with_mode(AcquireMode::Shed);
+    let app = Router::new()
+        .route(
+            "/",
+            get(|| async {
+                sleep(Duration::from_millis(100)).await;
+                "ok"
+            }),
+        )
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(
+                    |err: FlowError<std::convert::Infallible>| async move { err.into_response() },
+                ))
+                .layer(layer),
+        );
+
+    let request = || {
+        axum::http::Request::builder()
+            .uri("/")
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    let (first, second) = tokio::join!(app.clone().oneshot(request()), async {
+        sleep(Duration::from_millis(20)).await;
+        app.clone().oneshot(request()).await
+    });
+
+    assert_eq!(first.unwrap().status(), StatusCode::OK);
+    assert_eq!(second.unwrap().status(), StatusCode::SERVICE_UNAVAILABLE);
+}
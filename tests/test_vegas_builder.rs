@@ -0,0 +1,163 @@
+//! `VegasStrategy::builder()`: limites, RTT base inicial e limiares por função.
+
+use flow_guard::{ConfigError, LimitStrategy, VegasStrategy};
+use std::time::Duration;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn defaults_match_vegas_new() {
+    let built = VegasStrategy::builder().initial_limit(5).build().unwrap();
+    let classic = VegasStrategy::new(5);
+
+    for latency in [ms(50), ms(10), ms(10), ms(80), ms(12)] {
+        built.on_success(latency);
+        classic.on_success(latency);
+        assert_eq!(built.current_limit(), classic.current_limit());
+    }
+}
+
+#[test]
+fn respects_configured_bounds() {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(10)
+        .min_limit(8)
+        .max_limit(12)
+        .build()
+        .unwrap();
+
+    for _ in 0..50 {
+        strategy.on_success(ms(10));
+    }
+    assert_eq!(strategy.current_limit(), 12);
+
+    for _ in 0..50 {
+        strategy.on_error();
+    }
+    assert_eq!(strategy.current_limit(), 8);
+}
+
+#[test]
+fn initial_base_rtt_is_used_before_faster_samples() {
+    // Com RTT base de 10ms, uma amostra de 40ms indica fila grande e reduz o limite
+    let strategy = VegasStrategy::builder()
+        .initial_limit(20)
+        .initial_base_rtt(ms(10))
+        .build()
+        .unwrap();
+
+    strategy.on_success(ms(40));
+
+    assert_eq!(strategy.current_limit(), 19);
+}
+
+#[test]
+fn log10_thresholds_scale_with_limit() {
+    let build = |initial: usize| {
+        VegasStrategy::builder()
+            .initial_limit(initial)
+            .max_limit(100_000)
+            .initial_base_rtt(ms(10))
+            .alpha_fn(|limit| 3.0 * (limit as f64).log10())
+            .beta_fn(|limit| 6.0 * (limit as f64).log10())
+            .build()
+            .unwrap()
+    };
+
+    // 10% de latência extra: fila estimada de 1 em 10 e de 909 em 10.000
+    let small = build(10);
+    small.on_success(ms(11));
+    assert_eq!(small.current_limit(), 11, "fila 0.9 < alpha 3 -> cresce");
+
+    let large = build(10_000);
+    large.on_success(ms(11));
+    assert_eq!(large.current_limit(), 9_999, "fila 909 > beta 24 -> reduz");
+
+    // Latência 0.1% acima: fila estimada de ~10 em 10.000, abaixo de alpha(12) -> cresce
+    let large = build(10_000);
+    large.on_success(Duration::from_micros(10_010));
+    assert_eq!(large.current_limit(), 10_001);
+}
+
+#[test]
+fn threshold_enables_fast_growth() {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(100)
+        .max_limit(10_000)
+        .initial_base_rtt(ms(10))
+        .alpha_fn(|limit| 3.0 * (limit as f64).log10())
+        .beta_fn(|limit| 6.0 * (limit as f64).log10())
+        .threshold_fn(|limit| (limit as f64).log10())
+        .build()
+        .unwrap();
+
+    // Sem fila: cresce beta(100) = 12 de uma vez
+    strategy.on_success(ms(10));
+
+    assert_eq!(strategy.current_limit(), 112);
+}
+
+#[test]
+fn rejects_invalid_combinations() {
+    assert_eq!(
+        VegasStrategy::builder().min_limit(0).build().err(),
+        Some(ConfigError::ZeroMinLimit)
+    );
+    assert_eq!(
+        VegasStrategy::builder()
+            .min_limit(50)
+            .max_limit(10)
+            .build()
+            .err(),
+        Some(ConfigError::MinAboveMax { min: 50, max: 10 })
+    );
+    assert_eq!(
+        VegasStrategy::builder()
+            .initial_limit(5)
+            .min_limit(10)
+            .max_limit(20)
+            .build()
+            .err(),
+        Some(ConfigError::InitialOutOfBounds {
+            initial: 5,
+            min: 10,
+            max: 20
+        })
+    );
+    assert_eq!(
+        VegasStrategy::builder()
+            .initial_base_rtt(Duration::ZERO)
+            .build()
+            .err(),
+        Some(ConfigError::ZeroBaseRtt)
+    );
+    assert!(matches!(
+        VegasStrategy::builder().alpha(5.0).beta(1.0).build(),
+        Err(ConfigError::AlphaAboveBeta { .. })
+    ));
+    assert!(matches!(
+        VegasStrategy::builder().threshold(3.0).build(),
+        Err(ConfigError::ThresholdAboveAlpha { .. })
+    ));
+    assert!(matches!(
+        VegasStrategy::builder().alpha(f64::NAN).build(),
+        Err(ConfigError::InvalidThreshold { name: "alpha", .. })
+    ));
+}
+
+#[test]
+fn config_error_messages_are_readable() {
+    let err = VegasStrategy::builder()
+        .min_limit(50)
+        .max_limit(10)
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "min_limit (50) is greater than max_limit (10)"
+    );
+}
@@ -0,0 +1,198 @@
+//! Envelhecimento do RTT base e sondagem periódica no `VegasStrategy`.
+
+use flow_guard::{ConfigError, LimitStrategy, VegasStrategy};
+use std::time::Duration;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn without_aging_a_lucky_sample_pins_base_rtt() {
+    let strategy = VegasStrategy::builder().initial_limit(50).build().unwrap();
+
+    strategy.on_success(ms(1));
+    for _ in 0..500 {
+        strategy.on_success(ms(20));
+    }
+
+    // O limite colapsa para perto do mínimo e fica lá
+    assert_eq!(strategy.base_rtt(), ms(1));
+    assert!(strategy.current_limit() <= 5);
+}
+
+#[test]
+fn windowed_min_lets_base_rtt_recover() {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(50)
+        .base_rtt_window(50)
+        .build()
+        .unwrap();
+
+    // Amostra "sortuda" seguida de latência estável mais alta
+    strategy.on_success(ms(1));
+    for _ in 0..200 {
+        strategy.on_success(ms(20));
+    }
+
+    assert_eq!(strategy.base_rtt(), ms(20));
+    // Sem fila estimada, o limite volta a crescer
+    let before = strategy.current_limit();
+    for _ in 0..20 {
+        strategy.on_success(ms(20));
+    }
+    assert!(strategy.current_limit() > before);
+}
+
+#[test]
+fn base_rtt_follows_dependency_to_slower_datacenter() {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(20)
+        .base_rtt_window(20)
+        .build()
+        .unwrap();
+
+    for _ in 0..100 {
+        strategy.on_success(ms(5));
+    }
+    assert_eq!(strategy.base_rtt(), ms(5));
+
+    for _ in 0..100 {
+        strategy.on_success(ms(30));
+    }
+    assert_eq!(strategy.base_rtt(), ms(30));
+}
+
+#[tokio::test(start_paused = true)]
+async fn probe_lowers_limit_and_remeasures_base_rtt() {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(40)
+        .max_limit(40)
+        .probe_interval(10)
+        .probe_jitter(0.0)
+        .probe_limit(2)
+        .probe_samples(3)
+        .build()
+        .unwrap();
+
+    assert!(strategy.last_probe().is_none());
+
+    // Primeira amostra agenda a sondagem; mais 10 a disparam
+    for _ in 0..11 {
+        strategy.on_success(ms(10));
+    }
+    assert!(strategy.is_probing());
+    assert_eq!(strategy.current_limit(), 2);
+
+    // Execução admitida antes da queda do limite: não vale como RTT sem carga
+    strategy.on_success(ms(3));
+    assert!(strategy.is_probing());
+
+    // Amostras sem carga durante a sondagem viram o novo RTT base
+    tokio::time::advance(ms(20)).await;
+    for latency in [ms(8), ms(6), ms(7)] {
+        strategy.on_success(latency);
+    }
+
+    assert!(!strategy.is_probing());
+    assert_eq!(strategy.last_probe(), Some(tokio::time::Instant::now()));
+    assert_eq!(strategy.base_rtt(), ms(6));
+    assert_eq!(strategy.current_limit(), 40);
+}
+
+#[tokio::test(start_paused = true)]
+async fn errors_during_probe_apply_to_restored_limit() {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(40)
+        .max_limit(40)
+        .probe_interval(5)
+        .probe_jitter(0.0)
+        .probe_samples(2)
+        .build()
+        .unwrap();
+
+    for _ in 0..6 {
+        strategy.on_success(ms(10));
+    }
+    assert!(strategy.is_probing());
+
+    strategy.on_error();
+    tokio::time::advance(ms(20)).await;
+    strategy.on_success(ms(10));
+    strategy.on_success(ms(10));
+
+    assert_eq!(strategy.current_limit(), 30);
+}
+
+/// Amostras entre o fim de uma sondagem e o início da seguinte.
+async fn probe_intervals(seed: u64) -> Vec<usize> {
+    let strategy = VegasStrategy::builder()
+        .initial_limit(10)
+        .probe_interval(100)
+        .probe_jitter(1.0)
+        .probe_samples(1)
+        .probe_seed(seed)
+        .build()
+        .unwrap();
+
+    let mut intervals = Vec::new();
+    for _ in 0..6 {
+        let mut samples = 0;
+        while !strategy.is_probing() {
+            strategy.on_success(ms(10));
+            samples += 1;
+            assert!(samples <= 201);
+        }
+        intervals.push(samples);
+
+        // Uma amostra admitida durante a sondagem a encerra
+        tokio::time::advance(ms(20)).await;
+        strategy.on_success(ms(10));
+    }
+
+    // A primeira inclui a amostra que agenda a sondagem
+    intervals.remove(0);
+    intervals
+}
+
+#[tokio::test(start_paused = true)]
+async fn jitter_spreads_probes() {
+    let intervals = probe_intervals(7).await;
+
+    assert!(intervals.iter().all(|&n| (100..=200).contains(&n)));
+    assert!(
+        intervals.iter().any(|&n| n != intervals[0]),
+        "intervals should differ: {intervals:?}"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn probe_seed_makes_schedule_reproducible() {
+    assert_eq!(probe_intervals(42).await, probe_intervals(42).await);
+    assert_ne!(probe_intervals(42).await, probe_intervals(43).await);
+}
+
+#[test]
+fn rejects_invalid_probe_settings() {
+    assert!(matches!(
+        VegasStrategy::builder().base_rtt_window(0).build(),
+        Err(ConfigError::InvalidParameter {
+            name: "base_rtt_window",
+            ..
+        })
+    ));
+    assert!(matches!(
+        VegasStrategy::builder().probe_jitter(2.0).build(),
+        Err(ConfigError::InvalidParameter {
+            name: "probe_jitter",
+            ..
+        })
+    ));
+    assert!(matches!(
+        VegasStrategy::builder().probe_samples(0).build(),
+        Err(ConfigError::InvalidParameter {
+            name: "probe_samples",
+            ..
+        })
+    ));
+}
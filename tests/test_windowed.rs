@@ -0,0 +1,141 @@
+//! `WindowedStrategy`: uma amostra agregada por janela para a estratégia interna.
+
+mod common;
+
+use common::Recorder;
+use flow_guard::strategy::Aggregation;
+use flow_guard::{AimdStrategy, FlowGuard, LimitStrategy, WindowedStrategy};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn forwards_one_sample_per_window() {
+    let strategy = WindowedStrategy::new(Recorder::new(10)).with_sample_window(10);
+
+    for i in 0..35 {
+        strategy.on_success(ms(i));
+    }
+
+    assert_eq!(strategy.inner().latencies().len(), 3);
+}
+
+#[test]
+fn min_and_average_aggregations() {
+    let min = WindowedStrategy::new(Recorder::new(10))
+        .with_sample_window(4)
+        .with_aggregation(Aggregation::Min);
+    let avg = WindowedStrategy::new(Recorder::new(10))
+        .with_sample_window(4)
+        .with_aggregation(Aggregation::Average);
+
+    for latency in [ms(10), ms(20), ms(30), ms(100)] {
+        min.on_success(latency);
+        avg.on_success(latency);
+    }
+
+    assert_eq!(min.inner().latencies()[0], ms(10));
+    assert_eq!(avg.inner().latencies()[0], ms(40));
+}
+
+#[test]
+fn percentile_ignores_isolated_outlier() {
+    let strategy = WindowedStrategy::new(Recorder::new(10))
+        .with_sample_window(100)
+        .with_aggregation(Aggregation::Percentile(90.0));
+
+    for i in 0..100 {
+        strategy.on_success(if i == 50 { ms(5_000) } else { ms(10) });
+    }
+
+    let p90 = strategy.inner().latencies()[0];
+    // Histograma logarítmico: ~19% de resolução em torno de 10ms
+    assert!(p90 >= ms(8) && p90 <= ms(12), "p90 = {p90:?}");
+}
+
+#[test]
+fn percentile_tracks_tail() {
+    let strategy = WindowedStrategy::new(Recorder::new(10))
+        .with_sample_window(100)
+        .with_aggregation(Aggregation::Percentile(99.0));
+
+    for i in 0..100 {
+        strategy.on_success(if i < 95 { ms(10) } else { ms(200) });
+    }
+
+    let p99 = strategy.inner().latencies()[0];
+    assert!(p99 >= ms(160) && p99 <= ms(240), "p99 = {p99:?}");
+}
+
+#[test]
+fn errors_in_window_become_single_on_error() {
+    let strategy = WindowedStrategy::new(Recorder::new(10)).with_sample_window(5);
+
+    strategy.on_success(ms(10));
+    strategy.on_error();
+    strategy.on_error();
+    strategy.on_success(ms(10));
+    strategy.on_success(ms(10));
+
+    assert_eq!(strategy.inner().errors(), 1);
+    assert!(strategy.inner().latencies().is_empty());
+}
+
+#[test]
+fn isolated_error_passes_latency_aggregate() {
+    let strategy = WindowedStrategy::new(Recorder::new(10)).with_sample_window(20);
+
+    // 1 erro em 20 (5%) fica abaixo do limiar padrão de 10%
+    strategy.on_error();
+    for _ in 0..19 {
+        strategy.on_success(ms(10));
+    }
+
+    assert_eq!(strategy.inner().errors(), 0);
+    assert_eq!(strategy.inner().latencies(), [ms(10)]);
+}
+
+#[test]
+fn zero_error_threshold_counts_any_error() {
+    let strategy = WindowedStrategy::new(Recorder::new(10))
+        .with_sample_window(20)
+        .with_error_threshold(0.0);
+
+    strategy.on_error();
+    for _ in 0..19 {
+        strategy.on_success(ms(10));
+    }
+
+    assert_eq!(strategy.inner().errors(), 1);
+    assert!(strategy.inner().latencies().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn time_window_closes_partial_window() {
+    let strategy = WindowedStrategy::new(Recorder::new(10))
+        .with_sample_window(1_000)
+        .with_time_window(ms(20));
+
+    strategy.on_success(ms(5));
+    tokio::time::advance(ms(25)).await;
+    strategy.on_success(ms(7));
+
+    assert_eq!(strategy.inner().latencies().len(), 1);
+}
+
+#[tokio::test]
+async fn flow_guard_limit_changes_once_per_window() {
+    // AIMD soma 1 por amostra: 4 janelas, 4 incrementos
+    let strategy = Arc::new(WindowedStrategy::new(AimdStrategy::new(10)).with_sample_window(25));
+    let guard = FlowGuard::new(Arc::clone(&strategy));
+
+    for _ in 0..100 {
+        guard.run(async { Ok::<_, &str>(()) }).await.unwrap();
+    }
+
+    assert_eq!(guard.current_limit(), 14);
+    assert_eq!(guard.available_permits(), 14);
+}